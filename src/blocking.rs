@@ -0,0 +1,346 @@
+#![deny(missing_docs)]
+//! The Blocking module defines a synchronous Bot, for
+//! scripts and tools that do not need futures.
+//!
+//! Every action of the asynchronous Bot is available,
+//! running on a runtime managed by the blocking Bot,
+//! so it must not be used within an asynchronous runtime.
+//!
+//! ```rust,no_run
+//! use beepbop::blocking::Bot;
+//! use beepbop::input::SendMessage;
+//!
+//! let bot = Bot::new("API_KEY").expect("Cannot start the runtime");
+//!
+//! let me = bot.get_me().expect("Cannot get the bot user");
+//! let message = SendMessage::new(1234, format!("Hello from {}", me.first_name));
+//!
+//! bot.send_message(message).expect("Cannot send the message");
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+
+use crate::bot::{self, BotResult};
+use crate::input::*;
+use crate::object::*;
+
+/// Create a blocking version of an action of the
+/// asynchronous Bot, with the same arguments.
+macro_rules! blocking_action {
+    ($(#[$meta:meta])* $name:ident($($arg:ident: $arg_type:ty),*) -> $output:ty) => {
+        $(#[$meta])*
+        pub fn $name(&self, $($arg: $arg_type),*) -> BotResult<$output> {
+            self.runtime.block_on(self.bot.$name($($arg),*))
+        }
+    };
+}
+
+/// The blocking Bot wraps an asynchronous Bot and
+/// a runtime to wait for the result of every action.
+///
+/// Clones share the same connection and runtime.
+#[derive(Clone)]
+pub struct Bot {
+    bot: bot::Bot,
+    runtime: Arc<Runtime>,
+}
+
+impl std::fmt::Debug for Bot {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "Blocking{:?}", self.bot)
+    }
+}
+
+impl Bot {
+    /// Create a new blocking Bot using an API_KEY
+    pub fn new(api_key: &str) -> std::io::Result<Self> {
+        Bot::from_bot(bot::Bot::new(api_key))
+    }
+
+    /// Create a blocking Bot from an asynchronous Bot
+    pub fn from_bot(bot: bot::Bot) -> std::io::Result<Self> {
+        Ok(Bot {
+            bot,
+            runtime: Arc::new(Runtime::new()?),
+        })
+    }
+
+    /// The asynchronous Bot used by this Bot
+    pub fn inner(&self) -> &bot::Bot {
+        &self.bot
+    }
+
+    blocking_action!(
+        /// Download a file at telegram using it's ID
+        download_file(file_id: String) -> FileBuffer
+    );
+
+    blocking_action!(
+        /// Download a file at telegram into a file at path
+        download_file_to_path(file_id: String, path: impl AsRef<Path>, options: SaveOptions) -> u64
+    );
+
+    blocking_action!(
+        /// Send a file at telegram to a chat with another bot
+        transfer_file(file_id: String, to_bot: &bot::Bot, target: TransferTarget) -> Message
+    );
+
+    blocking_action!(
+        /// Send a video note to a telegram chat
+        send_video_note(send_video_note: SendVideoNote) -> Message
+    );
+
+    blocking_action!(
+        /// Send a chat action, like displaying 'sending photo' for a few seconds.
+        send_chat_action(id: impl Into<ChatID>, action: Action) -> bool
+    );
+
+    blocking_action!(
+        /// Send a document to a telegram chat
+        send_document(send_document: SendDocument) -> Message
+    );
+
+    /// Forward a message to a telegram chat by id
+    pub fn forward_message<ID: Into<ChatID>>(
+        &self,
+        chat_id: ID,
+        from_chat_id: ID,
+        message_id: i64,
+        disable_notification: bool,
+    ) -> BotResult<Message> {
+        self.runtime.block_on(self.bot.forward_message(
+            chat_id,
+            from_chat_id,
+            message_id,
+            disable_notification,
+        ))
+    }
+
+    blocking_action!(
+        /// Send a new message to a telegram chat
+        send_message(send_message: SendMessage) -> Message
+    );
+
+    blocking_action!(
+        /// Send a text longer than telegram allows in a single message
+        send_long_message(send_message: SendMessage) -> Vec<Message>
+    );
+
+    blocking_action!(
+        /// Leave a chat by id
+        leave_chat(id: impl Into<ChatID>) -> bool
+    );
+
+    blocking_action!(
+        /// Unban a chat member of a telegram chat
+        unban_chat_member(id: impl Into<ChatID>, user_id: i64) -> bool
+    );
+
+    blocking_action!(
+        /// Send a contact to a telegram chat
+        send_contact(send_contact: SendContact) -> Message
+    );
+
+    blocking_action!(
+        /// Get the count of members on a telegram chat
+        get_chat_members_count(id: impl Into<ChatID>) -> u64
+    );
+
+    blocking_action!(
+        /// Send an audio to a telegram chat
+        send_audio(send_audio: SendAudio) -> Message
+    );
+
+    blocking_action!(
+        /// Send a media group to a telegram chat
+        send_media_group(media_group: MediaGroup) -> Vec<Message>
+    );
+
+    blocking_action!(
+        /// Send media of any number and kind, split in the groups telegram accepts
+        send_media_album(album: MediaGroupBuilder) -> Vec<Message>
+    );
+
+    blocking_action!(
+        /// Get information about a chat by chat id
+        get_chat(id: impl Into<ChatID>) -> Chat
+    );
+
+    blocking_action!(
+        /// Kick a member of a telegram chat. Require kicking privileges
+        kick_chat_member(id: impl Into<ChatID>, user_id: i64, until_date: Option<u64>) -> bool
+    );
+
+    blocking_action!(
+        /// Get updates for your bot
+        get_updates(get_updates: GetUpdates) -> Vec<Update>
+    );
+
+    blocking_action!(
+        /// Send a location on a telegram chat
+        send_location(send_location: SendLocation) -> Message
+    );
+
+    blocking_action!(
+        /// Send a venue on a telegram chat
+        send_venue(send_venue: SendVenue) -> Message
+    );
+
+    blocking_action!(
+        /// Get the profile photos of an user
+        get_user_profile_photos(get_user_profile_photos: GetUserProfilePhotos) -> UserProfilePhotos
+    );
+
+    blocking_action!(
+        /// Edit an on going live location
+        edit_live_location(edit_live_location: EditLiveLocation) -> Message
+    );
+
+    blocking_action!(
+        /// Delete sticker set for a group or chat.
+        delete_chat_sticker_set(chat_id: impl Into<ChatID>) -> bool
+    );
+
+    blocking_action!(
+        /// Get information of a file on telegram
+        get_file(file_id: String) -> FileInfo
+    );
+
+    blocking_action!(
+        /// Unpin a message on a telegram chat
+        unpin_message(id: impl Into<ChatID>) -> bool
+    );
+
+    blocking_action!(
+        /// Set the textual description of a telegram chat
+        set_chat_description(id: impl Into<ChatID>, description: String) -> bool
+    );
+
+    blocking_action!(
+        /// Restrict a chat member by some defined parameters
+        restrict_chat_member(restrict_member: RestrictChatMember) -> bool
+    );
+
+    blocking_action!(
+        /// Promote a chat member.
+        promote_chat_member(promote_member: PromoteChatMember) -> bool
+    );
+
+    blocking_action!(
+        /// Get information about a chat member on a telegram chat
+        get_chat_member(chat_id: impl Into<ChatID>, user_id: i64) -> ChatMember
+    );
+
+    blocking_action!(
+        /// Delete the photo of a telegram chat
+        delete_chat_photo(chat_id: impl Into<ChatID>) -> bool
+    );
+
+    blocking_action!(
+        /// Send a voice to a telegram chat
+        send_voice(send_voice: SendVoice) -> Message
+    );
+
+    blocking_action!(
+        /// Get all admins of a telegram chat
+        get_chat_admins(chat_id: impl Into<ChatID>) -> Vec<ChatMember>
+    );
+
+    blocking_action!(
+        /// Set sticker set for a group or chat.
+        set_chat_sticker_set(chat_id: impl Into<ChatID>, sticker_set: &str) -> bool
+    );
+
+    blocking_action!(
+        /// Send a photo in telegram.
+        send_photo(send_photo: SendPhoto) -> Message
+    );
+
+    blocking_action!(
+        /// Export an invite link for the chat
+        export_chat_invite_link(id: impl Into<ChatID>) -> String
+    );
+
+    blocking_action!(
+        /// Get user information for your bot
+        get_me() -> User
+    );
+
+    blocking_action!(
+        /// Send a video on a telegram chat
+        send_video(send_video: SendVideo) -> Message
+    );
+
+    blocking_action!(
+        /// Set the title of a particular telegram chat
+        set_chat_title(id: impl Into<ChatID>, title: String) -> bool
+    );
+
+    blocking_action!(
+        /// Send an animation in a telegram chat
+        send_animation(send_animation: SendAnimation) -> Message
+    );
+
+    blocking_action!(
+        /// Stop an on going live location
+        stop_live_location(stop_live_location: StopLiveLocation) -> Message
+    );
+
+    blocking_action!(
+        /// Pin a message on a particular telegram chat
+        pin_message(id: impl Into<ChatID>, message_id: i64, disable_notification: bool) -> bool
+    );
+
+    blocking_action!(
+        /// Set the photo of a particular telegram chat
+        set_chat_photo(chat_id: impl Into<ChatID>, photo: FileUploader) -> bool
+    );
+
+    blocking_action!(
+        /// Send an invoice to a private chat.
+        send_invoice(send_invoice: SendInvoice) -> Message
+    );
+
+    blocking_action!(
+        /// Answer a shipping query
+        answer_shipping_query(answer_shipping_query: AnswerShippingQuery) -> bool
+    );
+
+    blocking_action!(
+        /// Answer a pre-checkout query
+        answer_precheckout_query(answer_precheckout_query: AnswerPrecheckoutQuery) -> bool
+    );
+
+    blocking_action!(
+        /// Answer a callback query
+        answer_callback_query(answer_callback_query: AnswerCallbackQuery) -> bool
+    );
+
+    blocking_action!(
+        /// Edit the inline keyboard of a message sent by the bot.
+        edit_message_reply_markup(edit_message_reply_markup: EditMessageReplyMarkup) -> Message
+    );
+
+    blocking_action!(
+        /// Edit the inline keyboard of an inline message.
+        edit_inline_reply_markup(edit_message_reply_markup: EditMessageReplyMarkup) -> bool
+    );
+}
+
+#[cfg(test)]
+mod blocking_tests {
+    use crate::blocking::Bot;
+    use std::env::var;
+
+    #[test]
+    fn get_me() {
+        let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
+
+        let bot = Bot::new(&api_key).expect("Cannot start the runtime");
+
+        assert!(bot.get_me().expect("Cannot get the bot user").is_bot);
+    }
+}
@@ -3,35 +3,38 @@
 //! object has all Telegram Actions available to him,
 //! as long as you have the required arguments.
 
+use std::path::Path;
 use std::sync::Arc;
 
-use futures::stream::Stream;
-
-use reqwest::r#async::Chunk;
-use reqwest::r#async::Client;
-use reqwest::r#async::Response;
+use futures::StreamExt;
+use reqwest::{Client, Response};
+use serde::Serialize;
+use tokio::io::AsyncWrite;
 
 use crate::error::BotError;
-use crate::object::FileBuffer;
+use crate::formatting::{split_text, MESSAGE_TEXT_LIMIT};
+use crate::object::{FileBuffer, FileStream};
 
 use crate::input::*;
 use crate::object::*;
-use crate::telegram_request::{Method, TelegramRequest};
-
-use futures::Future;
+use crate::telegram_request::TelegramRequest;
+use crate::upload_cache::{is_wrong_file_id, MediaKind, UploadCache};
 
 /// A wrapper for Bot related Results
 pub type BotResult<T> = Result<T, BotError>;
 
 /// The Connection struct holds data required for
 /// the Bot to communicate with the Telegram API.
+#[derive(Clone)]
 pub(crate) struct Connection {
     pub(crate) client: Client,
     api_key: String,
+    pub(crate) get_requests: bool,
+    upload_cache: Option<UploadCache>,
 }
 
-/// The Bot is a ARC over a connection, so piping is possible
-/// between Futures
+/// The Bot is a ARC over a connection, so it can be
+/// cloned and shared between tasks.
 #[derive(Clone)]
 pub struct Bot {
     pub(crate) connection: Arc<Connection>,
@@ -52,6 +55,8 @@ impl Bot {
         let connection = Connection {
             api_key: api_key.to_string(),
             client: Client::new(),
+            get_requests: false,
+            upload_cache: None,
         };
 
         Bot {
@@ -59,30 +64,179 @@ impl Bot {
         }
     }
 
+    /// Send requests without files as GET requests with
+    /// a query string instead of a json body.
+    ///
+    /// This is meant for debugging, as the arguments show
+    /// up on logs and long texts can go over URL limits.
+    pub fn with_get_requests(self) -> Self {
+        let mut connection = Connection::clone(&self.connection);
+        connection.get_requests = true;
+
+        Bot {
+            connection: Arc::new(connection),
+        }
+    }
+
+    /// Send files already uploaded once by their file_id,
+    /// instead of uploading the same content again.
+    pub fn with_upload_cache(self, upload_cache: UploadCache) -> Self {
+        let mut connection = Connection::clone(&self.connection);
+        connection.upload_cache = Some(upload_cache);
+
+        Bot {
+            connection: Arc::new(connection),
+        }
+    }
+
+    /// Send a message with a file within the telegram limits, through
+    /// the upload cache when the bot has one and the file is a local file.
+    async fn send_file<Q: Serialize>(
+        &self,
+        route: &str,
+        query: Q,
+        kind: MediaKind,
+        uploader: Uploader,
+    ) -> BotResult<Message> {
+        uploader.validate(kind, &self.connection.client).await?;
+
+        let request = |uploader: Uploader| {
+            TelegramRequest::new(self.get_route(route), self)
+                .with_input(&query)
+                .with_uploader(kind.field(), uploader)
+                .execute()
+        };
+
+        let (cache, file_uploader) = match (self.connection.upload_cache.as_ref(), uploader) {
+            (Some(cache), Uploader::File(file_uploader)) => (cache, file_uploader),
+            (_, uploader) => return request(uploader).await,
+        };
+
+        let key = match cache.key(self.bot_id(), kind, &file_uploader).await? {
+            Some(key) => key,
+            None => return request(Uploader::File(file_uploader)).await,
+        };
+
+        if let Some(cached) = cache.store().get(&key) {
+            match request(file_id(cached).into()).await {
+                Err(ref err) if is_wrong_file_id(err) => cache.store().remove(&key),
+                sent => return sent,
+            }
+        }
+
+        let message: Message = request(Uploader::File(file_uploader)).await?;
+
+        if let Some(file_id) = kind.file_id(&message) {
+            cache.store().insert(key, file_id);
+        }
+
+        Ok(message)
+    }
+
     /// Download a file at telegram using it's ID
-    pub fn download_file(
-        self,
+    pub async fn download_file(&self, file_id: String) -> BotResult<FileBuffer> {
+        let (file_path, _, response) = self.request_file(file_id).await?;
+        let data = response.bytes().await?;
+
+        Ok(FileBuffer::new(file_path, data.to_vec()))
+    }
+
+    /// Download a file at telegram as a stream of chunks,
+    /// without keeping the whole file in memory.
+    pub async fn download_stream(&self, file_id: String) -> BotResult<FileStream> {
+        let (file_path, size, response) = self.request_file(file_id).await?;
+
+        let size = size.or_else(|| response.content_length());
+        let chunks = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(BotError::from));
+
+        Ok(FileStream::new(file_path, size, chunks))
+    }
+
+    /// Start the download of a file, returning its
+    /// path and size with the response to read it from.
+    async fn request_file(&self, file_id: String) -> BotResult<(String, Option<u64>, Response)> {
+        let file_info = self.get_file(file_id).await?;
+        let file_path = file_info
+            .file_path
+            .ok_or(BotError::MissingFilePath(file_info.file_id))?;
+
+        let uri = self.get_file_uri(&file_path);
+        let response = self
+            .connection
+            .client
+            .get(&uri)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok((file_path, file_info.file_size, response))
+    }
+
+    /// Send a file at telegram to a chat with another bot, piping
+    /// the download into the upload without buffering the file.
+    ///
+    /// Bots with the same token send the file_id instead,
+    /// as file ids only work for the bot that received them.
+    pub async fn transfer_file(
+        &self,
         file_id: String,
-    ) -> impl Future<Item = (Self, FileBuffer), Error = BotError> {
-        self.get_file(file_id).and_then(move |(bot, file_info)| {
-            let file_path = file_info
-                .file_path
-                .expect("API download file without file_path");
+        to_bot: &Bot,
+        target: TransferTarget,
+    ) -> BotResult<Message> {
+        let (query, kind) = target.split();
+
+        if self.connection.api_key == to_bot.connection.api_key {
+            let uploader = crate::input::file_id(file_id).into();
+
+            return to_bot.send_file(kind.route(), query, kind, uploader).await;
+        }
 
-            let uri = bot.get_file_uri(&file_path);
+        let file_stream = self.download_stream(file_id).await?;
+        let uploader = FileUploader::from_file_stream(file_stream);
 
-            bot.connection
-                .client
-                .get(&uri)
-                .send()
-                .and_then(|response: Response| response.into_body().concat2())
-                .map(move |chunks: Chunk| {
-                    let file_buffer = FileBuffer::new(file_path, chunks.to_vec());
+        to_bot
+            .send_file(kind.route(), query, kind, uploader.into())
+            .await
+    }
 
-                    (bot, file_buffer)
-                })
-                .map_err(std::convert::Into::into)
-        })
+    /// Download a file at telegram into a writer, chunk by chunk,
+    /// returning the number of bytes written.
+    ///
+    /// Progress can be followed with download_stream and
+    /// FileStream::with_progress instead.
+    pub async fn download_file_to<W>(&self, file_id: String, writer: &mut W) -> BotResult<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        self.download_stream(file_id).await?.write_to(writer).await
+    }
+
+    /// Download a file at telegram into a file at path, chunk
+    /// by chunk, returning the number of bytes written.
+    ///
+    /// With atomic options the download goes to a temporary
+    /// file, only renamed to path once it is complete.
+    pub async fn download_file_to_path<P: AsRef<Path>>(
+        &self,
+        file_id: String,
+        path: P,
+        options: SaveOptions,
+    ) -> BotResult<u64> {
+        self.download_stream(file_id)
+            .await?
+            .save_with(path, options)
+            .await
+    }
+
+    /// The id of the bot, the part of its token before the colon.
+    pub(crate) fn bot_id(&self) -> &str {
+        self.connection
+            .api_key
+            .split(':')
+            .next()
+            .unwrap_or_default()
     }
 
     #[inline]
@@ -110,53 +264,44 @@ impl Bot {
     }
 
     /// Send a video note to a telegram chat
-    pub fn send_video_note(
-        self,
-        send_video_note: SendVideoNote,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_video_note(&self, send_video_note: SendVideoNote) -> BotResult<Message> {
         let (query, uploader) = send_video_note.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendVideoNote"), self)
-            .with_query(query)
-            .with_uploader("video_note", uploader)
-            .execute()
+        self.send_file(&"sendVideoNote", query, MediaKind::VideoNote, uploader)
+            .await
     }
 
     /// Send a chat action, that is a special action that a bot can take for
     /// some time, like displaying 'sending photo' for a few seconds.
-    pub fn send_chat_action<ID: Into<ChatID>>(
-        self,
+    pub async fn send_chat_action<ID: Into<ChatID>>(
+        &self,
         id: ID,
         action: Action,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let arg = SendChatAction::new(id.into(), action);
 
-        TelegramRequest::new(Method::GET, self.get_route(&"sendChatAction"), self)
-            .with_query(arg)
+        TelegramRequest::new(self.get_route(&"sendChatAction"), self)
+            .with_input(arg)
             .execute()
+            .await
     }
 
     /// Send a document to a telegram chat
-    pub fn send_document(
-        self,
-        send_document: SendDocument,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_document(&self, send_document: SendDocument) -> BotResult<Message> {
         let (query, uploader) = send_document.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendDocument"), self)
-            .with_query(query)
-            .with_uploader("document", uploader)
-            .execute()
+        self.send_file(&"sendDocument", query, MediaKind::Document, uploader)
+            .await
     }
 
     /// Forward a message to a telegram chat by id
-    pub fn forward_message<ID: Into<ChatID>>(
-        self,
+    pub async fn forward_message<ID: Into<ChatID>>(
+        &self,
         chat_id: ID,
         from_chat_id: ID,
         message_id: i64,
         disable_notification: bool,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    ) -> BotResult<Message> {
         let args = ForwardMessage {
             chat_id: chat_id.into(),
             from_chat_id: from_chat_id.into(),
@@ -164,242 +309,312 @@ impl Bot {
             disable_notification,
         };
 
-        TelegramRequest::new(Method::GET, self.get_route(&"forwardMessage"), self)
-            .with_query(args)
+        TelegramRequest::new(self.get_route(&"forwardMessage"), self)
+            .with_input(args)
             .execute()
+            .await
     }
 
     /// Send a new message to a telegram chat
-    pub fn send_message(
-        self,
-        send_message: SendMessage,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"sendMessage"), self)
-            .with_query(send_message)
+    pub async fn send_message(&self, send_message: SendMessage) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"sendMessage"), self)
+            .with_input(send_message)
             .execute()
+            .await
+    }
+
+    /// Send a text longer than telegram allows in a single message,
+    /// split on paragraphs, lines or words.
+    ///
+    /// Only the first part replies to a message and
+    /// only the last part has the reply markup.
+    ///
+    /// Media captions are not split, captions over CAPTION_LIMIT
+    /// fail with BotError::CaptionTooLong before they are sent.
+    pub async fn send_long_message(&self, send_message: SendMessage) -> BotResult<Vec<Message>> {
+        let SendMessage {
+            chat_id,
+            text,
+            parse_mode,
+            disable_web_page_preview,
+            disable_notification,
+            reply_to_message_id,
+            mut reply_markup,
+        } = send_message;
+
+        let parts = split_text(&text, parse_mode, MESSAGE_TEXT_LIMIT);
+        let last_part = parts.len() - 1;
+
+        let messages: Vec<SendMessage> = parts
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| SendMessage {
+                chat_id: chat_id.clone(),
+                text,
+                parse_mode,
+                disable_web_page_preview,
+                disable_notification,
+                reply_to_message_id: reply_to_message_id.filter(|_| index == 0),
+                reply_markup: if index == last_part {
+                    reply_markup.take()
+                } else {
+                    None
+                },
+            })
+            .collect();
+
+        let mut sent = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            sent.push(self.send_message(message).await?);
+        }
+
+        Ok(sent)
     }
 
     /// Leave a chat by id
-    pub fn leave_chat<ID: Into<ChatID>>(
-        self,
-        id: ID,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"leaveChat"), self)
-            .with_query(id.into())
+    pub async fn leave_chat<ID: Into<ChatID>>(&self, id: ID) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"leaveChat"), self)
+            .with_field("chat_id", id.into())
             .execute()
+            .await
     }
 
     /// Unban a chat member of a telegram chat
-    pub fn unban_chat_member<ID: Into<ChatID>>(
-        self,
+    pub async fn unban_chat_member<ID: Into<ChatID>>(
+        &self,
         id: ID,
         user_id: i64,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let arg = UnbanChatMember {
             chat_id: id.into(),
             user_id,
         };
 
-        TelegramRequest::new(Method::GET, self.get_route(&"unbanChatMember"), self)
-            .with_query(arg)
+        TelegramRequest::new(self.get_route(&"unbanChatMember"), self)
+            .with_input(arg)
             .execute()
+            .await
     }
 
     /// Send a contact to a telegram chat
-    pub fn send_contact(
-        self,
-        send_contact: SendContact,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"sendContact"), self)
-            .with_query(send_contact)
+    pub async fn send_contact(&self, send_contact: SendContact) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"sendContact"), self)
+            .with_input(send_contact)
             .execute()
+            .await
     }
 
     /// Get the count of members on a telegram chat
-    pub fn get_chat_members_count<ID: Into<ChatID>>(
-        self,
-        id: ID,
-    ) -> impl Future<Item = (Self, u64), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"getChatMembersCount"), self)
-            .with_query(id.into())
+    pub async fn get_chat_members_count<ID: Into<ChatID>>(&self, id: ID) -> BotResult<u64> {
+        TelegramRequest::new(self.get_route(&"getChatMembersCount"), self)
+            .with_field("chat_id", id.into())
             .execute()
+            .await
     }
 
     /// Send an audio to a telegram chat
-    pub fn send_audio(
-        self,
-        send_audio: SendAudio,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_audio(&self, send_audio: SendAudio) -> BotResult<Message> {
         let (query, voice) = send_audio.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendAudio"), self)
-            .with_query(query)
-            .with_uploader("audio", voice)
-            .execute()
+        self.send_file(&"sendAudio", query, MediaKind::Audio, voice)
+            .await
     }
 
     /// Send a media group to a telegram chat, that is, a group of photos and videos
-    pub fn send_media_group(
-        self,
-        media_group: MediaGroup,
-    ) -> impl Future<Item = (Self, Vec<Message>), Error = BotError> {
-        if !media_group.attachments.is_empty() {
-            let mut req =
-                TelegramRequest::new(Method::POST, self.get_route(&"sendMediaGroup"), self)
-                    .with_query(media_group.query)
-                    // Media is encoded as a string with a json inside
-                    .with_form_text("media", &media_group.media_encoded);
-
-            for attachment in media_group.attachments {
-                req = req.with_uploader(&attachment.name, attachment.uploader);
+    pub async fn send_media_group(&self, media_group: MediaGroup) -> BotResult<Vec<Message>> {
+        for attachment in &media_group.attachments {
+            attachment.validate(&self.connection.client).await?;
+        }
+
+        let mut req = TelegramRequest::new(self.get_route(&"sendMediaGroup"), self)
+            .with_input(media_group.query)
+            // Media is encoded as a string with a json inside
+            .with_field("media", &media_group.media_encoded);
+
+        for attachment in media_group.attachments {
+            req = req.with_uploader(&attachment.name, attachment.uploader);
+        }
+
+        req.execute().await
+    }
+
+    /// Send media of any number and kind, split in order into the
+    /// groups telegram accepts, with lone media sent by themselves.
+    ///
+    /// Sending is not atomic: when a part fails after others were
+    /// sent, the error is a BotError::PartialAlbum with the messages
+    /// already sent, so the remaining media can be sent again.
+    pub async fn send_media_album(&self, album: MediaGroupBuilder) -> BotResult<Vec<Message>> {
+        let mut messages = Vec::new();
+
+        for part in album.split_album()? {
+            let sent = match part {
+                AlbumPart::Group(media_group) => self.send_media_group(media_group).await,
+                AlbumPart::Single(media) => self.send_single_media(*media).await.map(|m| vec![m]),
+            };
+
+            match sent {
+                Ok(sent) => messages.extend(sent),
+                Err(err) if messages.is_empty() => return Err(err),
+                Err(err) => return Err(BotError::PartialAlbum(messages, Box::new(err))),
             }
+        }
 
-            req.execute()
-        } else {
-            TelegramRequest::new(Method::POST, self.get_route(&"sendMediaGroup"), self)
-                .with_body(media_group.query)
-                .execute()
+        Ok(messages)
+    }
+
+    /// Send a media of an album by itself, with the
+    /// thumbnail of a media given by url or id.
+    async fn send_single_media(&self, media: SingleMedia) -> BotResult<Message> {
+        let SingleMedia {
+            kind,
+            query,
+            uploader,
+            thumbnail,
+        } = media;
+
+        match thumbnail {
+            Some(thumbnail) => {
+                uploader.validate(kind, &self.connection.client).await?;
+                thumbnail.validate_thumbnail().await?;
+
+                TelegramRequest::new(self.get_route(kind.route()), self)
+                    .with_input(query)
+                    .with_uploader(kind.field(), uploader)
+                    .with_uploader("thumb", thumbnail.into())
+                    .execute()
+                    .await
+            }
+            None => self.send_file(kind.route(), query, kind, uploader).await,
         }
     }
 
     /// Get information about a chat by chat id
-    pub fn get_chat<ID: Into<ChatID>>(
-        self,
-        id: ID,
-    ) -> impl Future<Item = (Self, Chat), Error = BotError> {
+    pub async fn get_chat<ID: Into<ChatID>>(&self, id: ID) -> BotResult<Chat> {
         let get_chat = GetChat::new(id.into());
 
-        TelegramRequest::new(Method::GET, self.get_route(&"getChat"), self)
-            .with_query(get_chat)
+        TelegramRequest::new(self.get_route(&"getChat"), self)
+            .with_input(get_chat)
             .execute()
+            .await
     }
 
     /// Kick a member of a telegram chat. Require kicking privileges
-    pub fn kick_chat_member<ID: Into<ChatID>>(
-        self,
+    pub async fn kick_chat_member<ID: Into<ChatID>>(
+        &self,
         id: ID,
         user_id: i64,
         until_date: Option<u64>,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let arg = KickChatMember {
             chat_id: id.into(),
             user_id,
             until_date,
         };
 
-        TelegramRequest::new(Method::GET, self.get_route(&"kickChatMember"), self)
-            .with_query(arg)
+        TelegramRequest::new(self.get_route(&"kickChatMember"), self)
+            .with_input(arg)
             .execute()
+            .await
     }
 
     /// Get updates for your bot, those updates can be of many kinds, check the Update struct
     /// for more information
-    pub fn get_updates(
-        self,
-        get_updates: GetUpdates,
-    ) -> impl Future<Item = (Self, Vec<Update>), Error = BotError> {
-        TelegramRequest::new(Method::POST, self.get_route(&"getUpdates"), self)
-            .with_body(get_updates)
+    pub async fn get_updates(&self, get_updates: GetUpdates) -> BotResult<Vec<Update>> {
+        TelegramRequest::new(self.get_route(&"getUpdates"), self)
+            .with_input(get_updates)
             .execute()
+            .await
     }
 
     /// Send a location on a telegram chat
-    pub fn send_location(
-        self,
-        send_location: SendLocation,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"sendLocation"), self)
-            .with_query(send_location)
+    pub async fn send_location(&self, send_location: SendLocation) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"sendLocation"), self)
+            .with_input(send_location)
             .execute()
+            .await
     }
 
     /// Send a venue on a telegram chat
-    pub fn send_venue(
-        self,
-        send_venue: SendVenue,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"sendVenue"), self)
-            .with_query(send_venue)
+    pub async fn send_venue(&self, send_venue: SendVenue) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"sendVenue"), self)
+            .with_input(send_venue)
             .execute()
+            .await
     }
 
     /// Get the profile photos of an user
-    pub fn get_user_profile_photos(
-        self,
+    pub async fn get_user_profile_photos(
+        &self,
         get_user_profile_photos: GetUserProfilePhotos,
-    ) -> impl Future<Item = (Self, UserProfilePhotos), Error = BotError> {
-        TelegramRequest::new(Method::POST, self.get_route(&"getUserProfilePhotos"), self)
-            .with_body(get_user_profile_photos)
+    ) -> BotResult<UserProfilePhotos> {
+        TelegramRequest::new(self.get_route(&"getUserProfilePhotos"), self)
+            .with_input(get_user_profile_photos)
             .execute()
+            .await
     }
 
     /// Edit an on going live location
-    pub fn edit_live_location(
-        self,
+    pub async fn edit_live_location(
+        &self,
         edit_live_location: EditLiveLocation,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(
-            Method::GET,
-            self.get_route(&"editMessageLiveLocation"),
-            self,
-        )
-        .with_query(edit_live_location)
-        .execute()
+    ) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"editMessageLiveLocation"), self)
+            .with_input(edit_live_location)
+            .execute()
+            .await
     }
 
     /// Delete sticker set for a group or chat.
     ///
     /// Will only work if there is a sticker set defined.
-    pub fn delete_chat_sticker_set<ID: Into<ChatID>>(
-        self,
-        chat_id: ID,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"deleteChatStickerSet"), self)
-            .with_query(chat_id.into())
+    pub async fn delete_chat_sticker_set<ID: Into<ChatID>>(&self, chat_id: ID) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"deleteChatStickerSet"), self)
+            .with_field("chat_id", chat_id.into())
             .execute()
+            .await
     }
 
     /// Get information of a file on telegram
-    pub fn get_file(
-        self,
-        file_id: String,
-    ) -> impl Future<Item = (Self, FileInfo), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"getFile"), self)
-            .with_query(GetFile::new(file_id))
+    pub async fn get_file(&self, file_id: String) -> BotResult<FileInfo> {
+        TelegramRequest::new(self.get_route(&"getFile"), self)
+            .with_input(GetFile::new(file_id))
             .execute()
+            .await
     }
 
     /// Unpin a message on a telegram chat
-    pub fn unpin_message<ID: Into<ChatID>>(
-        self,
-        id: ID,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"unpinChatMessage"), self)
-            .with_query(id.into())
+    pub async fn unpin_message<ID: Into<ChatID>>(&self, id: ID) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"unpinChatMessage"), self)
+            .with_field("chat_id", id.into())
             .execute()
+            .await
     }
 
     /// Set the textual description of a telegram chat
-    pub fn set_chat_description<ID: Into<ChatID>>(
-        self,
+    pub async fn set_chat_description<ID: Into<ChatID>>(
+        &self,
         id: ID,
         description: String,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let set_chat_description = SetChatDescription::new(id.into(), description);
 
-        TelegramRequest::new(Method::GET, self.get_route(&"setChatDescription"), self)
-            .with_query(set_chat_description)
+        TelegramRequest::new(self.get_route(&"setChatDescription"), self)
+            .with_input(set_chat_description)
             .execute()
+            .await
     }
 
     /// Restrict a chat member by some defined parameters
-    pub fn restrict_chat_member(
-        self,
+    pub async fn restrict_chat_member(
+        &self,
         restrict_member: RestrictChatMember,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"restrictChatMember"), self)
-            .with_query(restrict_member)
+    ) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"restrictChatMember"), self)
+            .with_input(restrict_member)
             .execute()
+            .await
     }
 
     /// Promote a chat member.
@@ -413,81 +628,71 @@ impl Bot {
     ///
     /// 1. Admins cannot demote/promote the creator of the group.
     /// 2. Admins cannot demote/promote other admins.
-    pub fn promote_chat_member(
-        self,
-        promote_member: PromoteChatMember,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"promoteChatMember"), self)
-            .with_query(promote_member)
+    pub async fn promote_chat_member(&self, promote_member: PromoteChatMember) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"promoteChatMember"), self)
+            .with_input(promote_member)
             .execute()
+            .await
     }
 
     /// Get information about a chat member on a telegram chat
-    pub fn get_chat_member<ID>(
-        self,
-        chat_id: ID,
-        user_id: i64,
-    ) -> impl Future<Item = (Self, ChatMember), Error = BotError>
+    pub async fn get_chat_member<ID>(&self, chat_id: ID, user_id: i64) -> BotResult<ChatMember>
     where
         ID: Into<ChatID>,
     {
         let get_chat_member = GetChatMember::new(chat_id.into(), user_id);
 
-        TelegramRequest::new(Method::GET, self.get_route(&"getChatMember"), self)
-            .with_query(get_chat_member)
+        TelegramRequest::new(self.get_route(&"getChatMember"), self)
+            .with_input(get_chat_member)
             .execute()
+            .await
     }
 
     /// Delete the photo of a telegram chat
-    pub fn delete_chat_photo<ID: Into<ChatID>>(
-        self,
-        chat_id: ID,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"deleteChatPhoto"), self)
-            .with_query(chat_id.into())
+    pub async fn delete_chat_photo<ID: Into<ChatID>>(&self, chat_id: ID) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"deleteChatPhoto"), self)
+            .with_field("chat_id", chat_id.into())
             .execute()
+            .await
     }
 
     /// Send a voice to a telegram chat
-    pub fn send_voice(
-        self,
-        send_voice: SendVoice,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_voice(&self, send_voice: SendVoice) -> BotResult<Message> {
         let (query, voice) = send_voice.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendVoice"), self)
-            .with_query(query)
-            .with_uploader("voice", voice)
-            .execute()
+        self.send_file(&"sendVoice", query, MediaKind::Voice, voice)
+            .await
     }
 
     /// Get all admins of a telegram chat
-    pub fn get_chat_admins<ID: Into<ChatID>>(
-        self,
+    pub async fn get_chat_admins<ID: Into<ChatID>>(
+        &self,
         chat_id: ID,
-    ) -> impl Future<Item = (Self, Vec<ChatMember>), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"getChatAdministrators"), self)
-            .with_query(chat_id.into())
+    ) -> BotResult<Vec<ChatMember>> {
+        TelegramRequest::new(self.get_route(&"getChatAdministrators"), self)
+            .with_field("chat_id", chat_id.into())
             .execute()
+            .await
     }
 
     /// Set sticker set for a group or chat.
     ///
     /// Will only work for groups of at least
     /// 100 members.
-    pub fn set_chat_sticker_set<ID: Into<ChatID>>(
-        self,
+    pub async fn set_chat_sticker_set<ID: Into<ChatID>>(
+        &self,
         chat_id: ID,
         sticker_set: &str,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let set_chat_sticker_set = SetChatStickerSet {
             sticker_set_name: sticker_set.to_string(),
             chat_id: chat_id.into(),
         };
 
-        TelegramRequest::new(Method::GET, self.get_route(&"setChatStickerSet"), self)
-            .with_query(set_chat_sticker_set)
+        TelegramRequest::new(self.get_route(&"setChatStickerSet"), self)
+            .with_input(set_chat_sticker_set)
             .execute()
+            .await
     }
 
     /// Send a photo in telegram.
@@ -495,123 +700,179 @@ impl Bot {
     /// Photos can be uploaded by Id, Url and Post
     /// methods. Note that chat photo id's are only
     /// usable for downloading a chat photo, not here.
-    pub fn send_photo(
-        self,
-        send_photo: SendPhoto,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_photo(&self, send_photo: SendPhoto) -> BotResult<Message> {
         let (query, uploader) = send_photo.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendPhoto"), self)
-            .with_query(query)
-            .with_uploader("photo", uploader)
-            .execute()
+        self.send_file(&"sendPhoto", query, MediaKind::Photo, uploader)
+            .await
     }
 
     /// Export an invite link for the chat
-    pub fn export_chat_invite_link<ID: Into<ChatID>>(
-        self,
-        id: ID,
-    ) -> impl Future<Item = (Self, String), Error = BotError> {
+    pub async fn export_chat_invite_link<ID: Into<ChatID>>(&self, id: ID) -> BotResult<String> {
         let arg = ExportChatInviteLink::new(id.into());
 
-        TelegramRequest::new(Method::GET, self.get_route(&"exportChatInviteLink"), self)
-            .with_query(arg)
+        TelegramRequest::new(self.get_route(&"exportChatInviteLink"), self)
+            .with_input(arg)
             .execute()
+            .await
     }
 
     /// Get user information for your bot
-    pub fn get_me(self) -> impl Future<Item = (Self, User), Error = BotError> {
-        TelegramRequest::new(Method::GET, self.get_route(&"getMe"), self).execute()
+    pub async fn get_me(&self) -> BotResult<User> {
+        TelegramRequest::new(self.get_route(&"getMe"), self)
+            .execute()
+            .await
     }
 
     /// Send a video on a telegram chat
-    pub fn send_video(
-        self,
-        send_video: SendVideo,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_video(&self, send_video: SendVideo) -> BotResult<Message> {
         let (query, uploader) = send_video.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"SendVideo"), self)
-            .with_query(query)
-            .with_uploader("video", uploader)
-            .execute()
+        self.send_file(&"SendVideo", query, MediaKind::Video, uploader)
+            .await
     }
 
     /// Set the title of a particular telegram chat
-    pub fn set_chat_title<ID: Into<ChatID>>(
-        self,
-        id: ID,
-        title: String,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    pub async fn set_chat_title<ID: Into<ChatID>>(&self, id: ID, title: String) -> BotResult<bool> {
         let set_chat_title = SetChatTitle::new(id.into(), title);
 
-        TelegramRequest::new(Method::GET, self.get_route(&"setChatTitle"), self)
-            .with_query(set_chat_title)
+        TelegramRequest::new(self.get_route(&"setChatTitle"), self)
+            .with_input(set_chat_title)
             .execute()
+            .await
     }
 
     /// Send an animation in a telegram chat
-    pub fn send_animation(
-        self,
-        send_animation: SendAnimation,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+    pub async fn send_animation(&self, send_animation: SendAnimation) -> BotResult<Message> {
         let (query, uploader) = send_animation.split();
 
-        TelegramRequest::new(Method::POST, self.get_route(&"sendAnimation"), self)
-            .with_query(query)
-            .with_uploader("animation", uploader)
-            .execute()
+        self.send_file(&"sendAnimation", query, MediaKind::Animation, uploader)
+            .await
     }
 
     /// Stop an on going live location
-    pub fn stop_live_location(
-        self,
+    pub async fn stop_live_location(
+        &self,
         stop_live_location: StopLiveLocation,
-    ) -> impl Future<Item = (Self, Message), Error = BotError> {
-        TelegramRequest::new(
-            Method::GET,
-            self.get_route(&"stopMessageLiveLocation"),
-            self,
-        )
-        .with_query(stop_live_location)
-        .execute()
+    ) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"stopMessageLiveLocation"), self)
+            .with_input(stop_live_location)
+            .execute()
+            .await
     }
 
     /// Pin a message on a particular telegram chat
-    pub fn pin_message<ID: Into<ChatID>>(
-        self,
+    pub async fn pin_message<ID: Into<ChatID>>(
+        &self,
         id: ID,
         message_id: i64,
         disable_notification: bool,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+    ) -> BotResult<bool> {
         let pin_message = PinMessage::new(id.into(), message_id, disable_notification);
 
-        TelegramRequest::new(Method::GET, self.get_route(&"pinChatMessage"), self)
-            .with_query(pin_message)
+        TelegramRequest::new(self.get_route(&"pinChatMessage"), self)
+            .with_input(pin_message)
             .execute()
+            .await
     }
 
     /// Set the photo of a particular telegram chat
-    pub fn set_chat_photo<ID: Into<ChatID>>(
-        self,
+    pub async fn set_chat_photo<ID: Into<ChatID>>(
+        &self,
         chat_id: ID,
         photo: FileUploader,
-    ) -> impl Future<Item = (Self, bool), Error = BotError> {
-        TelegramRequest::new(Method::POST, self.get_route(&"setChatPhoto"), self)
-            .with_query(chat_id.into())
+    ) -> BotResult<bool> {
+        photo.validate(MediaKind::Photo).await?;
+
+        TelegramRequest::new(self.get_route(&"setChatPhoto"), self)
+            .with_field("chat_id", chat_id.into())
             .with_uploader("photo", photo.into())
             .execute()
+            .await
+    }
+
+    /// Send an invoice to a private chat.
+    ///
+    /// The invoice payload will be repeated on the
+    /// shipping and pre-checkout queries of this order.
+    pub async fn send_invoice(&self, send_invoice: SendInvoice) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"sendInvoice"), self)
+            .with_input(send_invoice)
+            .execute()
+            .await
+    }
+
+    /// Answer a shipping query, only needed
+    /// for invoices with flexible prices.
+    pub async fn answer_shipping_query(
+        &self,
+        answer_shipping_query: AnswerShippingQuery,
+    ) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"answerShippingQuery"), self)
+            .with_input(answer_shipping_query)
+            .execute()
+            .await
+    }
+
+    /// Answer a pre-checkout query, confirming or
+    /// refusing the order before the payment.
+    ///
+    /// Telegram expects this answer within 10 seconds.
+    pub async fn answer_precheckout_query(
+        &self,
+        answer_precheckout_query: AnswerPrecheckoutQuery,
+    ) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"answerPreCheckoutQuery"), self)
+            .with_input(answer_precheckout_query)
+            .execute()
+            .await
+    }
+
+    /// Answer a callback query, which must be done even
+    /// without a notification so the button stops loading.
+    pub async fn answer_callback_query(
+        &self,
+        answer_callback_query: AnswerCallbackQuery,
+    ) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"answerCallbackQuery"), self)
+            .with_input(answer_callback_query)
+            .execute()
+            .await
+    }
+
+    /// Edit the inline keyboard of a message sent by the bot.
+    ///
+    /// Edits of inline messages are answered with true instead
+    /// of a message, so those should use edit_inline_reply_markup.
+    pub async fn edit_message_reply_markup(
+        &self,
+        edit_message_reply_markup: EditMessageReplyMarkup,
+    ) -> BotResult<Message> {
+        TelegramRequest::new(self.get_route(&"editMessageReplyMarkup"), self)
+            .with_input(edit_message_reply_markup)
+            .execute()
+            .await
+    }
+
+    /// Edit the inline keyboard of an inline message.
+    pub async fn edit_inline_reply_markup(
+        &self,
+        edit_message_reply_markup: EditMessageReplyMarkup,
+    ) -> BotResult<bool> {
+        TelegramRequest::new(self.get_route(&"editMessageReplyMarkup"), self)
+            .with_input(edit_message_reply_markup)
+            .execute()
+            .await
     }
 }
 
 #[cfg(test)]
 mod bot_tests {
-    use crate::bot::Bot;
-    use crate::error::BotError;
+    use crate::bot::{Bot, BotResult};
     use crate::input::*;
     use crate::object::message::Message;
+    use crate::object::LabeledPrice;
 
-    use futures::Future;
     use std::env::var;
     use std::error::Error;
     use tokio::runtime::Runtime;
@@ -626,7 +887,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let video_note = file("res/video.mp4").unwrap();
 
@@ -647,7 +908,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let animation = file("res/anim.gif").unwrap();
 
@@ -668,7 +929,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let puppy_photo = file("res/puppy.jpg").unwrap();
 
@@ -689,7 +950,7 @@ mod bot_tests {
         let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(error) = runtime.block_on(bot.get_me()) {
             panic!(error.description().to_owned());
@@ -706,7 +967,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(error) = runtime.block_on(bot.send_chat_action(chat_id, Action::RecordAudio)) {
             panic!(error.description().to_owned());
@@ -729,7 +990,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(err) = runtime.block_on(bot.forward_message(chat_id, chat_id, message_id, false))
         {
@@ -753,7 +1014,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(error) = runtime.block_on(bot.kick_chat_member(chat_id, user_id, None)) {
             panic!(error.description().to_owned());
@@ -770,7 +1031,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let pupper_thumbnail = file("res/puppy.jpg")
             .and_then(add_mime("image/jpg"))
@@ -804,7 +1065,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(error) = runtime.block_on(bot.unban_chat_member(chat_id, user_id)) {
             panic!(error.description().to_owned());
@@ -821,7 +1082,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let voice = file("res/sound.mp3").unwrap();
 
@@ -843,7 +1104,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(err) = runtime.block_on(bot.get_chat(chat_id)) {
             panic!("{:#?}", err);
@@ -860,7 +1121,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let pupper_file = file("res/brownpuppy.png").unwrap();
         let pupper_two = file("res/puppy.jpg").unwrap();
@@ -879,6 +1140,27 @@ mod bot_tests {
         }
     }
 
+    #[test]
+    fn send_long_message() {
+        let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
+        let chat_id: i64 = var("CHAT_ID")
+            .expect("Cannot find CHAT_ID in ENV")
+            .parse()
+            .expect("CHAT_ID is not an valid ID.");
+
+        let bot = Bot::new(&api_key);
+
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+
+        let text = "<b>Beep bop</b> ".repeat(400);
+        let arg = SendMessage::new(chat_id, text).with_parse_mode(ParseMode::Html);
+
+        match runtime.block_on(bot.send_long_message(arg)) {
+            Ok(messages) => assert_eq!(messages.len(), 2),
+            Err(err) => panic!("{:#?}", err),
+        }
+    }
+
     #[test]
     fn send_venue() {
         let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
@@ -890,7 +1172,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let arg =
             SendVenue::new(chat_id, 20.0, 30.5, "Dunno", "Somewhere").with_foursquare_id("sddd");
@@ -910,7 +1192,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let arg = GetUserProfilePhotos::new(user_id).with_limit(2);
 
@@ -929,7 +1211,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         let voice = file("res/voice.ogg").unwrap();
 
@@ -940,10 +1222,7 @@ mod bot_tests {
         }
     }
 
-    fn send_photo_future(
-        file_name: &str,
-        mime_string: Option<&str>,
-    ) -> impl Future<Item = (Bot, Message), Error = BotError> {
+    async fn send_photo_future(file_name: &str, mime_string: Option<&str>) -> BotResult<Message> {
         let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
         let chat_id: i64 = var("CHAT_ID")
             .expect("Cannot find CHAT_ID in ENV")
@@ -959,12 +1238,12 @@ mod bot_tests {
         }
 
         let arg = SendPhoto::new(chat_id, uploader_res.unwrap());
-        bot.send_photo(arg)
+        bot.send_photo(arg).await
     }
 
     #[test]
     fn send_photo() {
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(err) =
             runtime.block_on(send_photo_future("res/brownpuppy.png", Some("image/png")))
@@ -975,12 +1254,49 @@ mod bot_tests {
 
     #[test]
     fn send_photo_without_mime() {
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
         if let Err(err) = runtime.block_on(send_photo_future("res/brownpuppy.png", None)) {
             panic!("{:#?}", err);
         }
     }
 
+    #[test]
+    #[ignore]
+    fn send_invoice() {
+        let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
+        let provider_token = var("PROVIDER_TOKEN").expect("Cannot find PROVIDER_TOKEN in ENV");
+        let chat_id: i64 = var("CHAT_ID")
+            .expect("Cannot find CHAT_ID in ENV")
+            .parse()
+            .expect("CHAT_ID is not an valid ID.");
+
+        let bot = Bot::new(&api_key);
+
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+
+        let prices = vec![
+            LabeledPrice::new("Puppy", 1000),
+            LabeledPrice::new("Collar", 250),
+        ];
+
+        let arg = SendInvoice::new(
+            chat_id,
+            "Puppy",
+            "A brown puppy",
+            "order-1",
+            provider_token.as_str(),
+            "puppy",
+            "USD",
+            prices,
+        )
+        .with_need_shipping_address(true)
+        .with_is_flexible(true);
+
+        if let Err(err) = runtime.block_on(bot.send_invoice(arg)) {
+            panic!("{:#?}", err);
+        }
+    }
+
     #[test]
     fn export_chat_invite_link() {
         let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
@@ -992,7 +1308,7 @@ mod bot_tests {
 
         let bot = Bot::new(&api_key);
 
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         if let Err(err) = runtime.block_on(bot.export_chat_invite_link(chat_id)) {
             panic!("{:#?}", err);
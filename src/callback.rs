@@ -0,0 +1,397 @@
+//! The Callback module provides a codec for typed
+//! callback data, so inline buttons can carry any
+//! serializable value instead of hand made strings.
+//!
+//! Values are encoded as compact JSON, optionally signed
+//! with an HMAC to detect tampered callback queries. Values
+//! too large for the 64 bytes allowed by Telegram are kept
+//! on a CallbackStore and sent as a short id.
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use hmac_sha256::HMAC;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::input::CALLBACK_DATA_LIMIT;
+use crate::object::CallbackQuery;
+
+/// Prefix of values encoded on the callback data itself.
+const INLINE_PREFIX: char = ':';
+/// Prefix of values kept on the CallbackStore.
+const STORED_PREFIX: char = '#';
+/// Bytes of the HMAC kept on the callback data.
+const SIGNATURE_BYTES: usize = 8;
+/// Length of the base64 encoded signature.
+const SIGNATURE_LENGTH: usize = 11;
+
+/// The reason a callback data could not be encoded or decoded.
+#[derive(Debug)]
+pub enum CallbackError {
+    /// The callback query has no data.
+    MissingData,
+    /// The value is over 64 bytes and there is no store to keep it.
+    TooLong(usize),
+    /// The signature does not match the data.
+    InvalidSignature,
+    /// The data is not in the format of this codec.
+    InvalidFormat,
+    /// The stored value was evicted or never existed.
+    NotFound(String),
+    /// The value could not be serialized or deserialized.
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            CallbackError::MissingData => "Callback query without data.".fmt(f),
+            CallbackError::TooLong(length) => write!(
+                f,
+                "Callback data must have at most {} bytes, found {}.",
+                CALLBACK_DATA_LIMIT, length
+            ),
+            CallbackError::InvalidSignature => "Invalid callback data signature.".fmt(f),
+            CallbackError::InvalidFormat => "Invalid callback data format.".fmt(f),
+            CallbackError::NotFound(id) => write!(f, "Callback data {} not found.", id),
+            CallbackError::Json(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CallbackError {}
+
+impl From<serde_json::Error> for CallbackError {
+    fn from(err: serde_json::Error) -> Self {
+        CallbackError::Json(err)
+    }
+}
+
+/// A CallbackStore keeps encoded values too large
+/// to be sent on the callback data, by short id.
+pub trait CallbackStore: Send + Sync {
+    /// Keep an encoded value, returning its id.
+    fn insert(&self, data: String) -> String;
+    /// Get an encoded value by its id.
+    fn get(&self, id: &str) -> Option<String>;
+}
+
+/// A CallbackStore backed by an in memory HashMap,
+/// evicting the oldest values once it is full.
+#[derive(Debug)]
+pub struct MemoryCallbackStore {
+    capacity: usize,
+    /// A random prefix of the ids, so ids of other stores
+    /// or previous processes are never found on this one.
+    nonce: u32,
+    next_id: AtomicUsize,
+    entries: Mutex<(HashMap<String, String>, VecDeque<String>)>,
+}
+
+impl MemoryCallbackStore {
+    pub fn new() -> Self {
+        MemoryCallbackStore::with_capacity(1024)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        MemoryCallbackStore {
+            capacity: capacity.max(1),
+            nonce: random_nonce(),
+            next_id: AtomicUsize::new(0),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+}
+
+impl Default for MemoryCallbackStore {
+    fn default() -> Self {
+        MemoryCallbackStore::new()
+    }
+}
+
+impl CallbackStore for MemoryCallbackStore {
+    fn insert(&self, data: String) -> String {
+        let id = format!(
+            "{:08x}{:x}",
+            self.nonce,
+            self.next_id.fetch_add(1, Ordering::SeqCst)
+        );
+
+        if let Ok(mut entries) = self.entries.lock() {
+            let (values, order) = &mut *entries;
+
+            while order.len() >= self.capacity {
+                if let Some(oldest) = order.pop_front() {
+                    values.remove(&oldest);
+                }
+            }
+
+            values.insert(id.clone(), data);
+            order.push_back(id.clone());
+        }
+
+        id
+    }
+
+    fn get(&self, id: &str) -> Option<String> {
+        self.entries
+            .lock()
+            .ok()
+            .and_then(|entries| entries.0.get(id).cloned())
+    }
+}
+
+/// A random number, from the random keys std gives to every hasher.
+fn random_nonce() -> u32 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|time| time.as_nanos())
+            .unwrap_or_default(),
+    );
+
+    hasher.finish() as u32
+}
+
+/// The CallbackCodec encodes values into callback data
+/// and decodes callback queries back into values.
+///
+/// ```
+/// use beepbop::callback::CallbackCodec;
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// enum Action {
+///     Page(u32),
+///     Select(String),
+/// }
+///
+/// let codec = CallbackCodec::new().with_key("secret");
+///
+/// let data = codec.encode(&Action::Page(2)).unwrap();
+/// assert_eq!(codec.decode::<Action>(&data).unwrap(), Action::Page(2));
+/// ```
+#[derive(Clone, Default)]
+pub struct CallbackCodec {
+    key: Option<Vec<u8>>,
+    store: Option<Arc<dyn CallbackStore>>,
+}
+
+impl std::fmt::Debug for CallbackCodec {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "CallbackCodec")
+    }
+}
+
+impl CallbackCodec {
+    pub fn new() -> Self {
+        CallbackCodec::default()
+    }
+
+    /// Sign the callback data with an HMAC of this key,
+    /// rejecting data not encoded by this codec.
+    pub fn with_key<K: AsRef<[u8]>>(mut self, key: K) -> Self {
+        self.key = Some(key.as_ref().to_vec());
+
+        self
+    }
+
+    /// Keep values too large for the callback data on a store.
+    pub fn with_store<S: CallbackStore + 'static>(mut self, store: S) -> Self {
+        self.store = Some(Arc::new(store));
+
+        self
+    }
+
+    /// Encode a value into callback data.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<String, CallbackError> {
+        let json = serde_json::to_string(value)?;
+        let data = self.sign(format!("{}{}", INLINE_PREFIX, json));
+
+        if data.len() <= CALLBACK_DATA_LIMIT {
+            return Ok(data);
+        }
+
+        match self.store.as_ref() {
+            Some(store) => {
+                let id = store.insert(json);
+                let data = self.sign(format!("{}{}", STORED_PREFIX, id));
+
+                if data.len() <= CALLBACK_DATA_LIMIT {
+                    Ok(data)
+                } else {
+                    Err(CallbackError::TooLong(data.len()))
+                }
+            }
+            None => Err(CallbackError::TooLong(data.len())),
+        }
+    }
+
+    /// Decode callback data into a value.
+    pub fn decode<T: DeserializeOwned>(&self, data: &str) -> Result<T, CallbackError> {
+        let payload = self.verify(data)?;
+
+        if payload.starts_with(INLINE_PREFIX) {
+            Ok(serde_json::from_str(&payload[1..])?)
+        } else if payload.starts_with(STORED_PREFIX) {
+            let id = &payload[1..];
+
+            let json = self
+                .store
+                .as_ref()
+                .and_then(|store| store.get(id))
+                .ok_or_else(|| CallbackError::NotFound(id.to_string()))?;
+
+            Ok(serde_json::from_str(&json)?)
+        } else {
+            Err(CallbackError::InvalidFormat)
+        }
+    }
+
+    /// Decode the data of a callback query into a value.
+    pub fn decode_query<T: DeserializeOwned>(
+        &self,
+        query: &CallbackQuery,
+    ) -> Result<T, CallbackError> {
+        match query.data.as_ref() {
+            Some(data) => self.decode(data),
+            None => Err(CallbackError::MissingData),
+        }
+    }
+
+    fn signature(&self, key: &[u8], payload: &str) -> String {
+        let mac = HMAC::mac(payload.as_bytes(), key);
+
+        base64::encode_config(&mac[..SIGNATURE_BYTES], base64::URL_SAFE_NO_PAD)
+    }
+
+    fn sign(&self, payload: String) -> String {
+        match self.key.as_ref() {
+            Some(key) => self.signature(key, &payload) + &payload,
+            None => payload,
+        }
+    }
+
+    fn verify<'a>(&self, data: &'a str) -> Result<&'a str, CallbackError> {
+        let key = match self.key.as_ref() {
+            Some(key) => key,
+            None => return Ok(data),
+        };
+
+        if data.len() < SIGNATURE_LENGTH || !data.is_char_boundary(SIGNATURE_LENGTH) {
+            return Err(CallbackError::InvalidSignature);
+        }
+
+        let (signature, payload) = data.split_at(SIGNATURE_LENGTH);
+        let expected = self.signature(key, payload);
+
+        // Compare every byte so the time taken does not
+        // tell how much of the signature is right.
+        let difference = signature
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b));
+
+        if difference == 0 {
+            Ok(payload)
+        } else {
+            Err(CallbackError::InvalidSignature)
+        }
+    }
+}
+
+#[cfg(test)]
+mod callback_tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Action {
+        Page(u32),
+        Select { id: String, note: String },
+    }
+
+    #[test]
+    fn encode_inline() {
+        let codec = CallbackCodec::new();
+
+        let data = codec.encode(&Action::Page(3)).unwrap();
+        assert_eq!(data, r#":{"Page":3}"#);
+        assert_eq!(codec.decode::<Action>(&data).unwrap(), Action::Page(3));
+    }
+
+    #[test]
+    fn detect_tampering() {
+        let codec = CallbackCodec::new().with_key("secret");
+
+        let data = codec.encode(&Action::Page(3)).unwrap();
+        assert_eq!(data.len(), SIGNATURE_LENGTH + 11);
+        assert_eq!(codec.decode::<Action>(&data).unwrap(), Action::Page(3));
+
+        let tampered = data.replace('3', "4");
+        assert!(matches!(
+            codec.decode::<Action>(&tampered),
+            Err(CallbackError::InvalidSignature)
+        ));
+
+        let other_key = CallbackCodec::new().with_key("other");
+        assert!(other_key.decode::<Action>(&data).is_err());
+    }
+
+    #[test]
+    fn store_large_values() {
+        let action = Action::Select {
+            id: "order-1234567890".to_string(),
+            note: "x".repeat(64),
+        };
+
+        assert!(matches!(
+            CallbackCodec::new().encode(&action),
+            Err(CallbackError::TooLong(_))
+        ));
+
+        let codec = CallbackCodec::new()
+            .with_key("secret")
+            .with_store(MemoryCallbackStore::with_capacity(1));
+
+        let data = codec.encode(&action).unwrap();
+        assert!(data.len() <= CALLBACK_DATA_LIMIT);
+        assert_eq!(codec.decode::<Action>(&data).unwrap(), action);
+
+        // The only slot of the store is taken by the new value.
+        codec.encode(&action).unwrap();
+        assert!(matches!(
+            codec.decode::<Action>(&data),
+            Err(CallbackError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn stores_do_not_share_ids() {
+        let action = Action::Select {
+            id: "order-1234567890".to_string(),
+            note: "x".repeat(64),
+        };
+
+        // Stores of the same key, like a bot before and after a restart.
+        let before = CallbackCodec::new()
+            .with_key("secret")
+            .with_store(MemoryCallbackStore::new());
+        let after = CallbackCodec::new()
+            .with_key("secret")
+            .with_store(MemoryCallbackStore::new());
+
+        let stale = before.encode(&action).unwrap();
+        after.encode(&Action::Page(1)).unwrap();
+        after.encode(&action).unwrap();
+
+        assert!(matches!(
+            after.decode::<Action>(&stale),
+            Err(CallbackError::NotFound(_))
+        ));
+    }
+}
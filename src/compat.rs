@@ -0,0 +1,399 @@
+#![deny(missing_docs)]
+//! The Compat module keeps the combinator API of futures 0.1,
+//! for code written before the Bot moved to async functions.
+//!
+//! Every action takes the Bot by value and resolves to the Bot
+//! with the data of the action, so actions can be chained with
+//! and_then. The actions run on a runtime managed by the Bot,
+//! so these futures can be driven by any futures 0.1 executor.
+//!
+//! ```rust,no_run
+//! use beepbop::compat::Bot;
+//! use beepbop::futures01::Future;
+//! use beepbop::input::SendMessage;
+//!
+//! let bot = Bot::new("API_KEY").expect("Cannot start the runtime");
+//!
+//! let hello = bot.get_me().and_then(|(bot, me)| {
+//!     bot.send_message(SendMessage::new(1234, format!("Hello from {}", me.first_name)))
+//! });
+//!
+//! hello.wait().expect("Cannot send the message");
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::TryFutureExt;
+use futures01::Future;
+use tokio::runtime::Runtime;
+
+use crate::bot::{self, BotResult};
+use crate::error::BotError;
+use crate::input::*;
+use crate::object::*;
+
+/// Create a futures 0.1 version of an action of the
+/// asynchronous Bot, with the same arguments.
+macro_rules! compat_action {
+    ($(#[$meta:meta])* $name:ident($($arg:ident: $arg_type:ty),*) -> $output:ty) => {
+        $(#[$meta])*
+        pub fn $name(self, $($arg: $arg_type),*) -> impl Future<Item = (Self, $output), Error = BotError> {
+            let bot = self.bot.clone();
+
+            self.spawn(async move { bot.$name($($arg),*).await })
+        }
+    };
+}
+
+/// The compat Bot wraps an asynchronous Bot and the
+/// runtime running its actions for futures 0.1 code.
+///
+/// Clones share the same connection and runtime.
+#[derive(Clone)]
+pub struct Bot {
+    bot: bot::Bot,
+    runtime: Arc<Runtime>,
+}
+
+impl std::fmt::Debug for Bot {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "Compat{:?}", self.bot)
+    }
+}
+
+impl Bot {
+    /// Create a new compat Bot using an API_KEY
+    pub fn new(api_key: &str) -> std::io::Result<Self> {
+        Bot::from_bot(bot::Bot::new(api_key))
+    }
+
+    /// Create a compat Bot from an asynchronous Bot
+    pub fn from_bot(bot: bot::Bot) -> std::io::Result<Self> {
+        Ok(Bot {
+            bot,
+            runtime: Arc::new(Runtime::new()?),
+        })
+    }
+
+    /// The asynchronous Bot used by this Bot
+    pub fn inner(&self) -> &bot::Bot {
+        &self.bot
+    }
+
+    /// Run an action on the runtime, resolving to
+    /// this Bot with the data of the action.
+    fn spawn<F, T>(self, action: F) -> impl Future<Item = (Self, T), Error = BotError>
+    where
+        F: std::future::Future<Output = BotResult<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let handle = self.runtime.spawn(action);
+
+        Box::pin(async move {
+            match handle.await {
+                Ok(result) => result.map(|data| (self, data)),
+                // A panic on the action is a panic of this future.
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                // The runtime lives as long as this Bot, so
+                // actions are never cancelled.
+                Err(err) => unreachable!("{}", err),
+            }
+        })
+        .compat()
+    }
+
+    compat_action!(
+        /// Download a file at telegram using it's ID
+        download_file(file_id: String) -> FileBuffer
+    );
+
+    compat_action!(
+        /// Download a file at telegram into a file at path
+        download_file_to_path(
+            file_id: String,
+            path: impl AsRef<Path> + Send + 'static,
+            options: SaveOptions
+        ) -> u64
+    );
+
+    /// Send a file at telegram to a chat with another bot
+    pub fn transfer_file(
+        self,
+        file_id: String,
+        to_bot: bot::Bot,
+        target: TransferTarget,
+    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+        let bot = self.bot.clone();
+
+        self.spawn(async move { bot.transfer_file(file_id, &to_bot, target).await })
+    }
+
+    compat_action!(
+        /// Send a video note to a telegram chat
+        send_video_note(send_video_note: SendVideoNote) -> Message
+    );
+
+    compat_action!(
+        /// Send a chat action, like displaying 'sending photo' for a few seconds.
+        send_chat_action(id: impl Into<ChatID> + Send + 'static, action: Action) -> bool
+    );
+
+    compat_action!(
+        /// Send a document to a telegram chat
+        send_document(send_document: SendDocument) -> Message
+    );
+
+    /// Forward a message to a telegram chat by id
+    pub fn forward_message<ID: Into<ChatID>>(
+        self,
+        chat_id: ID,
+        from_chat_id: ID,
+        message_id: i64,
+        disable_notification: bool,
+    ) -> impl Future<Item = (Self, Message), Error = BotError> {
+        let bot = self.bot.clone();
+        let (chat_id, from_chat_id) = (chat_id.into(), from_chat_id.into());
+
+        self.spawn(async move {
+            bot.forward_message(chat_id, from_chat_id, message_id, disable_notification)
+                .await
+        })
+    }
+
+    compat_action!(
+        /// Send a new message to a telegram chat
+        send_message(send_message: SendMessage) -> Message
+    );
+
+    compat_action!(
+        /// Send a text longer than telegram allows in a single message
+        send_long_message(send_message: SendMessage) -> Vec<Message>
+    );
+
+    compat_action!(
+        /// Leave a chat by id
+        leave_chat(id: impl Into<ChatID> + Send + 'static) -> bool
+    );
+
+    compat_action!(
+        /// Unban a chat member of a telegram chat
+        unban_chat_member(id: impl Into<ChatID> + Send + 'static, user_id: i64) -> bool
+    );
+
+    compat_action!(
+        /// Send a contact to a telegram chat
+        send_contact(send_contact: SendContact) -> Message
+    );
+
+    compat_action!(
+        /// Get the count of members on a telegram chat
+        get_chat_members_count(id: impl Into<ChatID> + Send + 'static) -> u64
+    );
+
+    compat_action!(
+        /// Send an audio to a telegram chat
+        send_audio(send_audio: SendAudio) -> Message
+    );
+
+    compat_action!(
+        /// Send a media group to a telegram chat
+        send_media_group(media_group: MediaGroup) -> Vec<Message>
+    );
+
+    compat_action!(
+        /// Send media of any number and kind, split in the groups telegram accepts
+        send_media_album(album: MediaGroupBuilder) -> Vec<Message>
+    );
+
+    compat_action!(
+        /// Get information about a chat by chat id
+        get_chat(id: impl Into<ChatID> + Send + 'static) -> Chat
+    );
+
+    compat_action!(
+        /// Kick a member of a telegram chat. Require kicking privileges
+        kick_chat_member(id: impl Into<ChatID> + Send + 'static, user_id: i64, until_date: Option<u64>) -> bool
+    );
+
+    compat_action!(
+        /// Get updates for your bot
+        get_updates(get_updates: GetUpdates) -> Vec<Update>
+    );
+
+    compat_action!(
+        /// Send a location on a telegram chat
+        send_location(send_location: SendLocation) -> Message
+    );
+
+    compat_action!(
+        /// Send a venue on a telegram chat
+        send_venue(send_venue: SendVenue) -> Message
+    );
+
+    compat_action!(
+        /// Get the profile photos of an user
+        get_user_profile_photos(get_user_profile_photos: GetUserProfilePhotos) -> UserProfilePhotos
+    );
+
+    compat_action!(
+        /// Edit an on going live location
+        edit_live_location(edit_live_location: EditLiveLocation) -> Message
+    );
+
+    compat_action!(
+        /// Delete sticker set for a group or chat.
+        delete_chat_sticker_set(chat_id: impl Into<ChatID> + Send + 'static) -> bool
+    );
+
+    compat_action!(
+        /// Get information of a file on telegram
+        get_file(file_id: String) -> FileInfo
+    );
+
+    compat_action!(
+        /// Unpin a message on a telegram chat
+        unpin_message(id: impl Into<ChatID> + Send + 'static) -> bool
+    );
+
+    compat_action!(
+        /// Set the textual description of a telegram chat
+        set_chat_description(id: impl Into<ChatID> + Send + 'static, description: String) -> bool
+    );
+
+    compat_action!(
+        /// Restrict a chat member by some defined parameters
+        restrict_chat_member(restrict_member: RestrictChatMember) -> bool
+    );
+
+    compat_action!(
+        /// Promote a chat member.
+        promote_chat_member(promote_member: PromoteChatMember) -> bool
+    );
+
+    compat_action!(
+        /// Get information about a chat member on a telegram chat
+        get_chat_member(chat_id: impl Into<ChatID> + Send + 'static, user_id: i64) -> ChatMember
+    );
+
+    compat_action!(
+        /// Delete the photo of a telegram chat
+        delete_chat_photo(chat_id: impl Into<ChatID> + Send + 'static) -> bool
+    );
+
+    compat_action!(
+        /// Send a voice to a telegram chat
+        send_voice(send_voice: SendVoice) -> Message
+    );
+
+    compat_action!(
+        /// Get all admins of a telegram chat
+        get_chat_admins(chat_id: impl Into<ChatID> + Send + 'static) -> Vec<ChatMember>
+    );
+
+    /// Set sticker set for a group or chat.
+    pub fn set_chat_sticker_set<ID: Into<ChatID>>(
+        self,
+        chat_id: ID,
+        sticker_set: &str,
+    ) -> impl Future<Item = (Self, bool), Error = BotError> {
+        let bot = self.bot.clone();
+        let (chat_id, sticker_set) = (chat_id.into(), sticker_set.to_string());
+
+        self.spawn(async move { bot.set_chat_sticker_set(chat_id, &sticker_set).await })
+    }
+
+    compat_action!(
+        /// Send a photo in telegram.
+        send_photo(send_photo: SendPhoto) -> Message
+    );
+
+    compat_action!(
+        /// Export an invite link for the chat
+        export_chat_invite_link(id: impl Into<ChatID> + Send + 'static) -> String
+    );
+
+    compat_action!(
+        /// Get user information for your bot
+        get_me() -> User
+    );
+
+    compat_action!(
+        /// Send a video on a telegram chat
+        send_video(send_video: SendVideo) -> Message
+    );
+
+    compat_action!(
+        /// Set the title of a particular telegram chat
+        set_chat_title(id: impl Into<ChatID> + Send + 'static, title: String) -> bool
+    );
+
+    compat_action!(
+        /// Send an animation in a telegram chat
+        send_animation(send_animation: SendAnimation) -> Message
+    );
+
+    compat_action!(
+        /// Stop an on going live location
+        stop_live_location(stop_live_location: StopLiveLocation) -> Message
+    );
+
+    compat_action!(
+        /// Pin a message on a particular telegram chat
+        pin_message(id: impl Into<ChatID> + Send + 'static, message_id: i64, disable_notification: bool) -> bool
+    );
+
+    compat_action!(
+        /// Set the photo of a particular telegram chat
+        set_chat_photo(chat_id: impl Into<ChatID> + Send + 'static, photo: FileUploader) -> bool
+    );
+
+    compat_action!(
+        /// Send an invoice to a private chat.
+        send_invoice(send_invoice: SendInvoice) -> Message
+    );
+
+    compat_action!(
+        /// Answer a shipping query
+        answer_shipping_query(answer_shipping_query: AnswerShippingQuery) -> bool
+    );
+
+    compat_action!(
+        /// Answer a pre-checkout query
+        answer_precheckout_query(answer_precheckout_query: AnswerPrecheckoutQuery) -> bool
+    );
+
+    compat_action!(
+        /// Answer a callback query
+        answer_callback_query(answer_callback_query: AnswerCallbackQuery) -> bool
+    );
+
+    compat_action!(
+        /// Edit the inline keyboard of a message sent by the bot.
+        edit_message_reply_markup(edit_message_reply_markup: EditMessageReplyMarkup) -> Message
+    );
+
+    compat_action!(
+        /// Edit the inline keyboard of an inline message.
+        edit_inline_reply_markup(edit_message_reply_markup: EditMessageReplyMarkup) -> bool
+    );
+}
+
+#[cfg(test)]
+mod compat_tests {
+    use crate::compat::Bot;
+    use futures01::Future;
+    use std::env::var;
+
+    #[test]
+    fn get_me() {
+        let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
+
+        let bot = Bot::new(&api_key).expect("Cannot start the runtime");
+
+        match bot.get_me().wait() {
+            Ok((_, me)) => assert!(me.is_bot),
+            Err(err) => panic!("{:#?}", err),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 #![deny(missing_docs)]
 //! The Error module define telegram bot related errors
 
-use crate::object::Message;
+use crate::formatting::CAPTION_LIMIT;
+use crate::input::UploaderError;
+use crate::object::{MalformedUpdate, Message};
+use crate::payments::PaymentCompleted;
 use std::error::Error;
 use std::sync::mpsc;
 
@@ -17,6 +20,25 @@ pub enum BotError {
     DownloadError(String),
     /// An BotError related to invalid media group formattion
     InvalidMediaGroup(String),
+    /// An BotError related to keyboards outside of telegram limits
+    InvalidKeyboard(String),
+    /// An BotError related to inputs that cannot be encoded on a request
+    EncodeError(String),
+    /// An BotError related to reading or writing local files
+    IoError(std::io::Error),
+    /// An BotError related to files on telegram without a path to download them
+    MissingFilePath(String),
+    /// An BotError related to successful responses without a result
+    EmptyResult,
+    /// An BotError related to responses which are not telegram json, with the HTTP status
+    InvalidResponse(u16, String),
+    /// An BotError related to files outside of telegram upload limits
+    UploadError(UploaderError),
+    /// An BotError related to albums interrupted by an error
+    /// after some of its parts were sent, with the sent messages
+    PartialAlbum(Vec<Message>, Box<BotError>),
+    /// An BotError related to captions over the telegram limit, with their length
+    CaptionTooLong(usize),
 }
 
 impl From<reqwest::Error> for BotError {
@@ -25,6 +47,18 @@ impl From<reqwest::Error> for BotError {
     }
 }
 
+impl From<UploaderError> for BotError {
+    fn from(error: UploaderError) -> Self {
+        BotError::UploadError(error)
+    }
+}
+
+impl From<std::io::Error> for BotError {
+    fn from(error: std::io::Error) -> Self {
+        BotError::IoError(error)
+    }
+}
+
 impl Into<()> for BotError {
     fn into(self) {}
 }
@@ -36,6 +70,30 @@ impl std::fmt::Display for BotError {
             BotError::RequestError(err) => err.fmt(f),
             BotError::DownloadError(err) => err.fmt(f),
             BotError::InvalidMediaGroup(err) => err.fmt(f),
+            BotError::InvalidKeyboard(err) => err.fmt(f),
+            BotError::EncodeError(err) => err.fmt(f),
+            BotError::IoError(err) => err.fmt(f),
+            BotError::MissingFilePath(file_id) => {
+                write!(f, "File {} has no path to download it from.", file_id)
+            }
+            BotError::EmptyResult => "Telegram response without a result.".fmt(f),
+            BotError::InvalidResponse(status, err) => {
+                write!(f, "Invalid response with HTTP status {}: {}", status, err)
+            }
+            BotError::UploadError(err) => err.fmt(f),
+            BotError::PartialAlbum(sent, err) => {
+                write!(
+                    f,
+                    "Album interrupted after {} messages: {}",
+                    sent.len(),
+                    err
+                )
+            }
+            BotError::CaptionTooLong(length) => write!(
+                f,
+                "Caption of {} characters over the limit of {}",
+                length, CAPTION_LIMIT
+            ),
         }
     }
 }
@@ -47,11 +105,22 @@ impl Error for BotError {
             BotError::RequestError(err) => err.description(),
             BotError::DownloadError(err) => err,
             BotError::InvalidMediaGroup(err) => err,
+            BotError::InvalidKeyboard(err) => err,
+            BotError::EncodeError(err) => err,
+            BotError::IoError(_) => "IO error on a local file",
+            BotError::MissingFilePath(_) => "File without a path to download it from",
+            BotError::EmptyResult => "Telegram response without a result",
+            BotError::InvalidResponse(_, err) => err,
+            BotError::UploadError(_) => "File outside of telegram upload limits",
+            BotError::PartialAlbum(..) => "Album interrupted after some media were sent",
+            BotError::CaptionTooLong(_) => "Caption over the telegram limit",
         }
     }
 }
 
 type SendMessageError = mpsc::SendError<Message>;
+type SendPaymentError = mpsc::SendError<PaymentCompleted>;
+type SendMalformedError = mpsc::SendError<MalformedUpdate>;
 
 /// The StreamError is an error originated from a stream
 /// of updates.
@@ -61,6 +130,10 @@ pub enum StreamError {
     BotError(BotError),
     /// An error occurred while trying to send an message to a channel
     SendMessageError(SendMessageError),
+    /// An error occurred while trying to send a payment to a channel
+    SendPaymentError(Box<SendPaymentError>),
+    /// An error occurred while trying to send a malformed update to a channel
+    SendMalformedError(Box<SendMalformedError>),
 }
 
 impl From<BotError> for StreamError {
@@ -75,11 +148,25 @@ impl From<SendMessageError> for StreamError {
     }
 }
 
+impl From<SendPaymentError> for StreamError {
+    fn from(send_error: SendPaymentError) -> StreamError {
+        StreamError::SendPaymentError(Box::new(send_error))
+    }
+}
+
+impl From<SendMalformedError> for StreamError {
+    fn from(send_error: SendMalformedError) -> StreamError {
+        StreamError::SendMalformedError(Box::new(send_error))
+    }
+}
+
 impl std::fmt::Display for StreamError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
             StreamError::BotError(err) => err.fmt(f),
             StreamError::SendMessageError(err) => err.fmt(f),
+            StreamError::SendPaymentError(err) => err.fmt(f),
+            StreamError::SendMalformedError(err) => err.fmt(f),
         }
     }
 }
@@ -89,6 +176,18 @@ impl Error for StreamError {
         match self {
             StreamError::BotError(err) => err.description(),
             StreamError::SendMessageError(err) => err.description(),
+            StreamError::SendPaymentError(_) | StreamError::SendMalformedError(_) => {
+                "sending on a closed channel"
+            }
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StreamError::BotError(err) => Some(err),
+            StreamError::SendMessageError(err) => Some(err),
+            StreamError::SendPaymentError(err) => Some(err.as_ref()),
+            StreamError::SendMalformedError(err) => Some(err.as_ref()),
         }
     }
 }
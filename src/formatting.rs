@@ -0,0 +1,1064 @@
+//! The Formatting module turns the entities of a message
+//! into typed spans and renders formatted text back into
+//! the markup telegram understands.
+//!
+//! Telegram counts entity offsets in UTF-16 code units, the
+//! spans created here use byte ranges on the rust String.
+use std::ops::Range;
+
+use crate::error::BotError;
+use crate::input::{ChatID, ParseMode, SendMessage};
+use crate::object::{Message, MessageEntity, User};
+
+/// The kind of a message entity.
+#[derive(Clone, Debug)]
+pub enum EntityKind {
+    Mention,
+    Hashtag,
+    Cashtag,
+    BotCommand,
+    Url,
+    Email,
+    PhoneNumber,
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Code,
+    /// A code block with an optional programming language.
+    Pre(Option<String>),
+    /// A clickable text pointing to an url.
+    TextLink(String),
+    /// A mention of an user without username.
+    TextMention(User),
+    Unknown(String),
+}
+
+impl EntityKind {
+    pub fn from_entity(entity: &MessageEntity) -> Self {
+        match (entity.r#type.as_str(), &entity.url, &entity.user) {
+            ("mention", _, _) => EntityKind::Mention,
+            ("hashtag", _, _) => EntityKind::Hashtag,
+            ("cashtag", _, _) => EntityKind::Cashtag,
+            ("bot_command", _, _) => EntityKind::BotCommand,
+            ("url", _, _) => EntityKind::Url,
+            ("email", _, _) => EntityKind::Email,
+            ("phone_number", _, _) => EntityKind::PhoneNumber,
+            ("bold", _, _) => EntityKind::Bold,
+            ("italic", _, _) => EntityKind::Italic,
+            ("underline", _, _) => EntityKind::Underline,
+            ("strikethrough", _, _) => EntityKind::Strikethrough,
+            ("spoiler", _, _) => EntityKind::Spoiler,
+            ("code", _, _) => EntityKind::Code,
+            ("pre", _, _) => EntityKind::Pre(entity.language.clone()),
+            ("text_link", Some(url), _) => EntityKind::TextLink(url.clone()),
+            ("text_mention", _, Some(user)) => EntityKind::TextMention(user.clone()),
+            (other, _, _) => EntityKind::Unknown(other.to_string()),
+        }
+    }
+
+    /// Entities detected by telegram itself, like urls
+    /// and hashtags, need no markup to be sent again.
+    fn has_markup(&self) -> bool {
+        matches!(
+            self,
+            EntityKind::Bold
+                | EntityKind::Italic
+                | EntityKind::Underline
+                | EntityKind::Strikethrough
+                | EntityKind::Spoiler
+                | EntityKind::Code
+                | EntityKind::Pre(_)
+                | EntityKind::TextLink(_)
+                | EntityKind::TextMention(_)
+        )
+    }
+
+    fn is_code(&self) -> bool {
+        matches!(self, EntityKind::Code | EntityKind::Pre(_))
+    }
+}
+
+/// A Span is a typed entity with the text it covers.
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub kind: EntityKind,
+    /// The byte range of this span on the original text.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl Span {
+    /// The url this span points to, if any.
+    pub fn url(&self) -> Option<&str> {
+        match &self.kind {
+            EntityKind::Url => Some(&self.text),
+            EntityKind::TextLink(url) => Some(url),
+            _ => None,
+        }
+    }
+}
+
+/// A text with the typed spans of its entities.
+#[derive(Clone, Debug)]
+pub struct FormattedText {
+    pub text: String,
+    pub spans: Vec<Span>,
+}
+
+impl FormattedText {
+    /// Create a FormattedText from a text and its entities.
+    ///
+    /// Entities outside of the text are clamped to its end.
+    pub fn new<S: Into<String>>(text: S, entities: &[MessageEntity]) -> Self {
+        let text = text.into();
+
+        let spans = entities
+            .iter()
+            .map(|entity| {
+                let start = byte_offset(&text, entity.offset);
+                let end = byte_offset(&text, entity.offset.saturating_add(entity.length));
+
+                Span {
+                    kind: EntityKind::from_entity(entity),
+                    text: text[start..end].to_string(),
+                    range: start..end,
+                }
+            })
+            .collect();
+
+        FormattedText { text, spans }
+    }
+
+    /// The formatted text of a message, if it has any text.
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let entities = message.entities.as_ref().map_or(&[][..], Vec::as_slice);
+
+        message
+            .text
+            .as_ref()
+            .map(|text| FormattedText::new(text.as_str(), entities))
+    }
+
+    /// The formatted caption of a message, if it has any caption.
+    pub fn from_caption(message: &Message) -> Option<Self> {
+        let entities = message
+            .caption_entities
+            .as_ref()
+            .map_or(&[][..], Vec::as_slice);
+
+        message
+            .caption
+            .as_ref()
+            .map(|caption| FormattedText::new(caption.as_str(), entities))
+    }
+
+    /// All urls on the text, both plain urls and text links.
+    pub fn links(&self) -> Vec<&str> {
+        self.spans.iter().filter_map(Span::url).collect()
+    }
+
+    /// All mentions on the text, by username or not.
+    pub fn mentions(&self) -> Vec<&Span> {
+        self.spans
+            .iter()
+            .filter(|span| matches!(span.kind, EntityKind::Mention | EntityKind::TextMention(_)))
+            .collect()
+    }
+
+    /// Render the text as telegram HTML.
+    pub fn to_html(&self) -> String {
+        self.render(ParseMode::Html)
+    }
+
+    /// Render the text as telegram MarkdownV2.
+    pub fn to_markdown_v2(&self) -> String {
+        self.render(ParseMode::MarkdownV2)
+    }
+
+    /// Render the text with the markup of a parse mode.
+    ///
+    /// The legacy Markdown has no underline, strikethrough
+    /// or spoiler, so those entities are rendered as plain text.
+    pub fn render(&self, parse_mode: ParseMode) -> String {
+        let mut spans: Vec<&Span> = self
+            .spans
+            .iter()
+            .filter(|span| span.kind.has_markup() && !span.range.is_empty())
+            .collect();
+
+        // Outer spans come before the spans nested inside them.
+        spans.sort_by(|a, b| {
+            a.range
+                .start
+                .cmp(&b.range.start)
+                .then(b.range.end.cmp(&a.range.end))
+        });
+
+        let mut output = String::with_capacity(self.text.len());
+        let mut stack: Vec<(&EntityKind, usize)> = Vec::new();
+        let mut position = 0;
+
+        for span in spans {
+            while let Some(&(kind, end)) = stack.last() {
+                if end > span.range.start {
+                    break;
+                }
+
+                output.push_str(&self.escape_range(position..end, &stack, parse_mode));
+                output.push_str(&close_tag(kind, parse_mode));
+                position = end;
+                stack.pop();
+            }
+
+            output.push_str(&self.escape_range(position..span.range.start, &stack, parse_mode));
+            position = span.range.start;
+
+            // Telegram entities never overlap partially, but a
+            // malformed one is clipped to its parent.
+            let end = stack.last().map_or(span.range.end, |&(_, parent_end)| {
+                span.range.end.min(parent_end)
+            });
+
+            output.push_str(&open_tag(&span.kind, parse_mode));
+            stack.push((&span.kind, end));
+        }
+
+        while let Some(&(kind, end)) = stack.last() {
+            output.push_str(&self.escape_range(position..end, &stack, parse_mode));
+            output.push_str(&close_tag(kind, parse_mode));
+            position = end;
+            stack.pop();
+        }
+
+        output.push_str(&self.escape_range(position..self.text.len(), &stack, parse_mode));
+
+        output
+    }
+
+    fn escape_range(
+        &self,
+        range: Range<usize>,
+        stack: &[(&EntityKind, usize)],
+        parse_mode: ParseMode,
+    ) -> String {
+        let inside_code = stack.iter().any(|(kind, _)| kind.is_code());
+
+        escape(&self.text[range], parse_mode, inside_code)
+    }
+}
+
+fn open_tag(kind: &EntityKind, parse_mode: ParseMode) -> String {
+    match (parse_mode, kind) {
+        (ParseMode::Html, EntityKind::Bold) => "<b>".to_string(),
+        (ParseMode::Html, EntityKind::Italic) => "<i>".to_string(),
+        (ParseMode::Html, EntityKind::Underline) => "<u>".to_string(),
+        (ParseMode::Html, EntityKind::Strikethrough) => "<s>".to_string(),
+        (ParseMode::Html, EntityKind::Spoiler) => "<tg-spoiler>".to_string(),
+        (ParseMode::Html, EntityKind::Code) => "<code>".to_string(),
+        (ParseMode::Html, EntityKind::Pre(Some(language))) => {
+            format!("<pre><code class=\"language-{}\">", escape_html(language))
+        }
+        (ParseMode::Html, EntityKind::Pre(None)) => "<pre>".to_string(),
+        (ParseMode::Html, EntityKind::TextLink(url)) => {
+            format!("<a href=\"{}\">", escape_html(url))
+        }
+        (ParseMode::Html, EntityKind::TextMention(user)) => {
+            format!("<a href=\"tg://user?id={}\">", user.id)
+        }
+        (ParseMode::MarkdownV2, EntityKind::Bold) => "*".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Italic) => "_".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Underline) => "__".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Strikethrough) => "~".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Spoiler) => "||".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Code) => "`".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Pre(language)) => format!(
+            "```{}\n",
+            escape_markdown_v2_code(language.as_ref().map_or("", String::as_str))
+        ),
+        (ParseMode::MarkdownV2, EntityKind::TextLink(_))
+        | (ParseMode::MarkdownV2, EntityKind::TextMention(_)) => "[".to_string(),
+        (ParseMode::Markdown, EntityKind::Bold) => "*".to_string(),
+        (ParseMode::Markdown, EntityKind::Italic) => "_".to_string(),
+        (ParseMode::Markdown, EntityKind::Code) => "`".to_string(),
+        (ParseMode::Markdown, EntityKind::Pre(language)) => {
+            format!("```{}\n", language.as_ref().map_or("", String::as_str))
+        }
+        (ParseMode::Markdown, EntityKind::TextLink(_))
+        | (ParseMode::Markdown, EntityKind::TextMention(_)) => "[".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn close_tag(kind: &EntityKind, parse_mode: ParseMode) -> String {
+    match (parse_mode, kind) {
+        (ParseMode::Html, EntityKind::Bold) => "</b>".to_string(),
+        (ParseMode::Html, EntityKind::Italic) => "</i>".to_string(),
+        (ParseMode::Html, EntityKind::Underline) => "</u>".to_string(),
+        (ParseMode::Html, EntityKind::Strikethrough) => "</s>".to_string(),
+        (ParseMode::Html, EntityKind::Spoiler) => "</tg-spoiler>".to_string(),
+        (ParseMode::Html, EntityKind::Code) => "</code>".to_string(),
+        (ParseMode::Html, EntityKind::Pre(Some(_))) => "</code></pre>".to_string(),
+        (ParseMode::Html, EntityKind::Pre(None)) => "</pre>".to_string(),
+        (ParseMode::Html, EntityKind::TextLink(_))
+        | (ParseMode::Html, EntityKind::TextMention(_)) => "</a>".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::Pre(_)) => "\n```".to_string(),
+        (ParseMode::MarkdownV2, EntityKind::TextLink(url)) => {
+            format!("]({})", escape_markdown_v2_url(url))
+        }
+        (ParseMode::MarkdownV2, EntityKind::TextMention(user)) => {
+            format!("](tg://user?id={})", user.id)
+        }
+        (ParseMode::MarkdownV2, kind) => open_tag(kind, parse_mode),
+        (ParseMode::Markdown, EntityKind::Pre(_)) => "\n```".to_string(),
+        (ParseMode::Markdown, EntityKind::TextLink(url)) => format!("]({})", url),
+        (ParseMode::Markdown, EntityKind::TextMention(user)) => {
+            format!("](tg://user?id={})", user.id)
+        }
+        (ParseMode::Markdown, kind) => open_tag(kind, parse_mode),
+        _ => String::new(),
+    }
+}
+
+/// The TextBuilder creates a formatted text for a parse mode,
+/// escaping every piece of text added to it.
+///
+/// ```rust
+/// use beepbop::formatting::TextBuilder;
+/// use beepbop::input::ParseMode;
+///
+/// let text = TextBuilder::new(ParseMode::MarkdownV2)
+///     .text("Hello, ")
+///     .mention_user("Ada", 42)
+///     .text("! Your total is ")
+///     .bold("$1.50")
+///     .build();
+///
+/// assert_eq!(text, "Hello, [Ada](tg://user?id=42)\\! Your total is *$1\\.50*");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TextBuilder {
+    parse_mode: ParseMode,
+    output: String,
+}
+
+impl TextBuilder {
+    pub fn new(parse_mode: ParseMode) -> Self {
+        TextBuilder {
+            parse_mode,
+            output: String::new(),
+        }
+    }
+
+    /// The parse mode the text is being escaped for.
+    pub fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+
+    /// Add a plain text.
+    pub fn text(mut self, text: &str) -> Self {
+        self.output.push_str(&escape(text, self.parse_mode, false));
+
+        self
+    }
+
+    /// Add a markup string as is, without escaping it.
+    pub fn raw(mut self, markup: &str) -> Self {
+        self.output.push_str(markup);
+
+        self
+    }
+
+    pub fn bold(self, text: &str) -> Self {
+        self.entity(EntityKind::Bold, text)
+    }
+
+    pub fn italic(self, text: &str) -> Self {
+        self.entity(EntityKind::Italic, text)
+    }
+
+    pub fn underline(self, text: &str) -> Self {
+        self.entity(EntityKind::Underline, text)
+    }
+
+    pub fn strikethrough(self, text: &str) -> Self {
+        self.entity(EntityKind::Strikethrough, text)
+    }
+
+    pub fn spoiler(self, text: &str) -> Self {
+        self.entity(EntityKind::Spoiler, text)
+    }
+
+    pub fn code(self, text: &str) -> Self {
+        self.entity(EntityKind::Code, text)
+    }
+
+    /// Add a code block with an optional programming language.
+    pub fn pre(self, text: &str, language: Option<&str>) -> Self {
+        self.entity(EntityKind::Pre(language.map(str::to_owned)), text)
+    }
+
+    pub fn link(self, text: &str, url: &str) -> Self {
+        self.entity(EntityKind::TextLink(url.to_owned()), text)
+    }
+
+    /// Add a mention to an user by id, it works
+    /// even for users without an username.
+    pub fn mention_user(self, text: &str, user_id: i64) -> Self {
+        self.link(text, &format!("tg://user?id={}", user_id))
+    }
+
+    fn entity(mut self, kind: EntityKind, text: &str) -> Self {
+        if self.parse_mode == ParseMode::Markdown && !kind.is_code() {
+            return self.markdown_entity(kind, text);
+        }
+
+        self.output.push_str(&open_tag(&kind, self.parse_mode));
+        self.output
+            .push_str(&escape(text, self.parse_mode, kind.is_code()));
+        self.output.push_str(&close_tag(&kind, self.parse_mode));
+
+        self
+    }
+
+    /// The legacy Markdown allows no escapes inside an entity, so
+    /// the entity is closed before each reserved character and
+    /// opened again after it, as in `_snake_\__case_`.
+    fn markdown_entity(mut self, kind: EntityKind, text: &str) -> Self {
+        let pieces = text.split(|character| MARKDOWN_RESERVED.contains(character));
+        let mut reserved = text.matches(|character| MARKDOWN_RESERVED.contains(character));
+
+        for piece in pieces {
+            if !piece.is_empty() {
+                self.output.push_str(&open_tag(&kind, self.parse_mode));
+                self.output.push_str(piece);
+                self.output.push_str(&close_tag(&kind, self.parse_mode));
+            }
+
+            if let Some(character) = reserved.next() {
+                self.output.push('\\');
+                self.output.push_str(character);
+            }
+        }
+
+        self
+    }
+
+    /// Finish the text.
+    pub fn build(self) -> String {
+        self.output
+    }
+
+    /// Finish the text into a message with the right parse mode.
+    pub fn into_message<ID: Into<ChatID>>(self, chat_id: ID) -> SendMessage {
+        let parse_mode = self.parse_mode;
+
+        SendMessage::new(chat_id, self.build()).with_parse_mode(parse_mode)
+    }
+}
+
+/// The maximum length of a message text, after parsing its entities.
+pub const MESSAGE_TEXT_LIMIT: usize = 4096;
+
+/// The maximum length of a media caption, after parsing its entities.
+pub const CAPTION_LIMIT: usize = 1024;
+
+/// The entities open at some point of a text, with
+/// the markup that opens and closes each of them.
+type OpenEntities<'a> = Vec<(&'a str, String)>;
+
+/// A piece of a formatted text, used to split it.
+#[derive(Debug)]
+enum Token<'a> {
+    /// A visible piece of text with its length in UTF-16
+    /// code units, escapes are a single token.
+    Text(&'a str, usize),
+    /// A markup opening an entity, with the markup closing it.
+    Open(&'a str, String),
+    /// A markup closing the last opened entity.
+    Close(&'a str),
+}
+
+/// Split a text in parts of at most limit characters.
+///
+/// The text is split on paragraphs, then lines, then words.
+/// Entities crossing a split are closed at the end of a part
+/// and opened again on the next one, so every part is valid
+/// markup for the parse mode.
+///
+/// ```rust
+/// use beepbop::formatting::split_text;
+/// use beepbop::input::ParseMode;
+///
+/// let parts = split_text("<b>Bold words</b>", Some(ParseMode::Html), 8);
+///
+/// assert_eq!(parts, vec!["<b>Bold</b>", "<b>words</b>"]);
+/// ```
+pub fn split_text(text: &str, parse_mode: Option<ParseMode>, limit: usize) -> Vec<String> {
+    let tokens = tokenize(text, parse_mode);
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut reopened: OpenEntities = Vec::new();
+
+    loop {
+        let mut stack = reopened.clone();
+        let mut length = 0;
+        // The token to split at, its priority and the entities open on it.
+        let mut split: Option<(usize, u8, OpenEntities)> = None;
+        let mut index = start;
+
+        while index < tokens.len() {
+            match &tokens[index] {
+                Token::Text(piece, piece_length) => {
+                    if length > 0 && length + piece_length > limit {
+                        break;
+                    }
+
+                    let priority = match *piece {
+                        "\n" if index > 0 && is_newline(&tokens[index - 1]) => 3,
+                        "\n" => 2,
+                        " " | "\t" => 1,
+                        _ => 0,
+                    };
+
+                    let best_priority = split.as_ref().map_or(0, |&(_, best, _)| best);
+
+                    if length > 0 && priority > 0 && priority >= best_priority {
+                        split = Some((index, priority, stack.clone()));
+                    }
+
+                    length += piece_length;
+                }
+                Token::Open(open, close) => stack.push((open, close.clone())),
+                Token::Close(_) => {
+                    stack.pop();
+                }
+            }
+
+            index += 1;
+        }
+
+        if index == tokens.len() {
+            parts.push(render_part(&reopened, &tokens[start..], &stack));
+
+            return parts;
+        }
+
+        let (end, next_start, open_entities) = match split {
+            Some((split_index, _, open_entities)) => (split_index, split_index + 1, open_entities),
+            None => (index, index, stack),
+        };
+
+        parts.push(render_part(&reopened, &tokens[start..end], &open_entities));
+
+        start = next_start;
+        reopened = open_entities;
+    }
+}
+
+/// The length of a text as telegram counts it, in UTF-16
+/// code units once the markup of its entities is parsed.
+///
+/// ```rust
+/// use beepbop::formatting::text_length;
+/// use beepbop::input::ParseMode;
+///
+/// assert_eq!(text_length("<b>Bold</b> &amp; 🐶", Some(ParseMode::Html)), 9);
+/// ```
+pub fn text_length(text: &str, parse_mode: Option<ParseMode>) -> usize {
+    tokenize(text, parse_mode)
+        .iter()
+        .map(|token| match token {
+            Token::Text(_, length) => *length,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Check a caption fits within the caption limit of telegram.
+pub(crate) fn check_caption(caption: &str, parse_mode: Option<ParseMode>) -> Result<(), BotError> {
+    match text_length(caption, parse_mode) {
+        length if length > CAPTION_LIMIT => Err(BotError::CaptionTooLong(length)),
+        _ => Ok(()),
+    }
+}
+
+fn tokenize(text: &str, parse_mode: Option<ParseMode>) -> Vec<Token<'_>> {
+    match parse_mode {
+        Some(ParseMode::Html) => tokenize_html(text),
+        Some(ParseMode::MarkdownV2) => tokenize_markdown(text, true),
+        Some(ParseMode::Markdown) => tokenize_markdown(text, false),
+        None => tokenize_plain(text),
+    }
+}
+
+fn is_newline(token: &Token) -> bool {
+    match token {
+        Token::Text(piece, _) => *piece == "\n",
+        _ => false,
+    }
+}
+
+fn render_part(reopened: &[(&str, String)], tokens: &[Token], open: &[(&str, String)]) -> String {
+    let mut part = String::new();
+
+    for (open_markup, _) in reopened {
+        part.push_str(open_markup);
+    }
+
+    for token in tokens {
+        match token {
+            Token::Text(piece, _) | Token::Open(piece, _) | Token::Close(piece) => {
+                part.push_str(piece)
+            }
+        }
+    }
+
+    for (_, close_markup) in open.iter().rev() {
+        part.push_str(close_markup);
+    }
+
+    part
+}
+
+/// Take the first character of a text as a Text token.
+fn character_token(text: &str) -> (Token<'_>, usize) {
+    let character = text.chars().next().unwrap_or_default();
+    let size = character.len_utf8();
+
+    (Token::Text(&text[..size], character.len_utf16()), size)
+}
+
+fn tokenize_plain(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < text.len() {
+        let (token, size) = character_token(&text[position..]);
+
+        tokens.push(token);
+        position += size;
+    }
+
+    tokens
+}
+
+fn tokenize_html(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < text.len() {
+        let rest = &text[position..];
+
+        if rest.starts_with('<') {
+            let size = rest.find('>').map_or(rest.len(), |end| end + 1);
+            let tag = &rest[..size];
+
+            if tag.starts_with("</") {
+                tokens.push(Token::Close(tag));
+            } else {
+                let name = tag[1..]
+                    .split(|c: char| c.is_whitespace() || c == '>')
+                    .next()
+                    .unwrap_or_default();
+
+                tokens.push(Token::Open(tag, format!("</{}>", name)));
+            }
+
+            position += size;
+        } else if let Some(size) = html_entity_size(rest) {
+            tokens.push(Token::Text(&rest[..size], 1));
+            position += size;
+        } else {
+            let (token, size) = character_token(rest);
+
+            tokens.push(token);
+            position += size;
+        }
+    }
+
+    tokens
+}
+
+/// The size of an HTML entity like &amp; at the start of a text.
+fn html_entity_size(text: &str) -> Option<usize> {
+    if !text.starts_with('&') {
+        return None;
+    }
+
+    text.char_indices()
+        .take(10)
+        .find(|&(_, c)| c == ';')
+        .map(|(end, _)| end + 1)
+}
+
+fn tokenize_markdown(text: &str, v2: bool) -> Vec<Token<'_>> {
+    let markers: &[&str] = if v2 {
+        &["||", "__", "*", "_", "~", "`"]
+    } else {
+        &["*", "_", "`"]
+    };
+
+    let mut tokens = Vec::new();
+    // The markers of open entities and where open links end.
+    let mut stack: Vec<&str> = Vec::new();
+    let mut link_ends: Vec<(usize, usize)> = Vec::new();
+    let mut position = 0;
+
+    while position < text.len() {
+        let rest = &text[position..];
+        let inside_code = matches!(stack.last(), Some(marker) if marker.starts_with('`'));
+
+        if rest.starts_with('\\') && rest.len() > 1 {
+            let escaped = rest[1..].chars().next().unwrap_or_default();
+            let size = 1 + escaped.len_utf8();
+
+            tokens.push(Token::Text(&rest[..size], escaped.len_utf16()));
+            position += size;
+            continue;
+        }
+
+        if inside_code {
+            let closing = stack.last().copied().unwrap_or_default();
+
+            if rest.starts_with(closing) {
+                tokens.push(Token::Close(&rest[..closing.len()]));
+                stack.pop();
+                position += closing.len();
+            } else {
+                let (token, size) = character_token(rest);
+
+                tokens.push(token);
+                position += size;
+            }
+
+            continue;
+        }
+
+        if let Some(&(link_start, link_end)) = link_ends.last() {
+            if link_start == position {
+                tokens.push(Token::Close(&text[link_start..link_end]));
+                stack.pop();
+                link_ends.pop();
+                position = link_end;
+                continue;
+            }
+        }
+
+        if rest.starts_with("```") {
+            let size = rest.find('\n').map_or(3, |end| end + 1);
+
+            tokens.push(Token::Open(&rest[..size], "```".to_string()));
+            stack.push("```");
+            position += size;
+            continue;
+        }
+
+        if rest.starts_with('[') {
+            if let Some(link_end) = markdown_link_end(rest) {
+                let close = &rest[link_end.0..link_end.1];
+
+                tokens.push(Token::Open(&rest[..1], close.to_string()));
+                stack.push("[");
+                link_ends.push((position + link_end.0, position + link_end.1));
+                position += 1;
+                continue;
+            }
+        }
+
+        if let Some(marker) = markers.iter().find(|marker| rest.starts_with(**marker)) {
+            if stack.last() == Some(marker) {
+                tokens.push(Token::Close(marker));
+                stack.pop();
+            } else {
+                tokens.push(Token::Open(marker, marker.to_string()));
+                stack.push(marker);
+            }
+
+            position += marker.len();
+            continue;
+        }
+
+        let (token, size) = character_token(rest);
+
+        tokens.push(token);
+        position += size;
+    }
+
+    tokens
+}
+
+/// Find the "](url)" part of a markdown link starting the text.
+fn markdown_link_end(text: &str) -> Option<(usize, usize)> {
+    let url_start = text.find("](")?;
+    let mut escaped = false;
+
+    for (index, character) in text[url_start + 2..].char_indices() {
+        match character {
+            '\\' if !escaped => escaped = true,
+            ')' if !escaped => return Some((url_start, url_start + 2 + index + 1)),
+            _ => escaped = false,
+        }
+    }
+
+    None
+}
+
+/// Convert an offset in UTF-16 code units to a byte offset.
+fn byte_offset(text: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+
+    for (byte, character) in text.char_indices() {
+        if units >= utf16_offset {
+            return byte;
+        }
+
+        units += character.len_utf16();
+    }
+
+    text.len()
+}
+
+/// Escape a text for a parse mode, code entities
+/// have less characters to escape in markdown.
+fn escape(text: &str, parse_mode: ParseMode, inside_code: bool) -> String {
+    match (parse_mode, inside_code) {
+        (ParseMode::Html, _) => escape_html(text),
+        (ParseMode::MarkdownV2, true) => escape_markdown_v2_code(text),
+        (ParseMode::MarkdownV2, false) => escape_markdown_v2(text),
+        (ParseMode::Markdown, true) => text.to_string(),
+        (ParseMode::Markdown, false) => escape_markdown(text),
+    }
+}
+
+/// Escape a text to be used inside telegram HTML.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            other => escaped.push(other),
+        }
+    }
+
+    escaped
+}
+
+/// The characters with a meaning in the legacy telegram Markdown.
+const MARKDOWN_RESERVED: &str = "_*`[";
+
+/// Escape a text to be used inside the legacy telegram Markdown.
+pub fn escape_markdown(text: &str) -> String {
+    escape_with(text, MARKDOWN_RESERVED)
+}
+
+/// Escape a text to be used inside telegram MarkdownV2.
+pub fn escape_markdown_v2(text: &str) -> String {
+    escape_with(text, "_*[]()~`>#+-=|{}.!\\")
+}
+
+/// Escape a text to be used inside a MarkdownV2 code entity.
+fn escape_markdown_v2_code(text: &str) -> String {
+    escape_with(text, "`\\")
+}
+
+/// Escape an url to be used inside a MarkdownV2 link.
+fn escape_markdown_v2_url(text: &str) -> String {
+    escape_with(text, ")\\")
+}
+
+fn escape_with(text: &str, special_characters: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        if special_characters.contains(character) {
+            escaped.push('\\');
+        }
+
+        escaped.push(character);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod formatting_tests {
+    use super::*;
+
+    fn entity(r#type: &str, offset: usize, length: usize) -> MessageEntity {
+        MessageEntity {
+            r#type: r#type.to_string(),
+            offset,
+            length,
+            url: None,
+            user: None,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        // The dog emoji takes two UTF-16 code units.
+        let text = "🐶 @puppy likes #bones";
+        let formatted =
+            FormattedText::new(text, &[entity("mention", 3, 6), entity("hashtag", 16, 6)]);
+
+        assert_eq!(formatted.spans[0].text, "@puppy");
+        assert_eq!(formatted.spans[1].text, "#bones");
+        assert_eq!(formatted.mentions().len(), 1);
+    }
+
+    #[test]
+    fn clamp_oversized_entities() {
+        let formatted = FormattedText::new(
+            "beep bop",
+            &[
+                entity("bold", 5, usize::MAX),
+                entity("italic", usize::MAX, 1),
+            ],
+        );
+
+        assert_eq!(formatted.spans[0].text, "bop");
+        assert_eq!(formatted.spans[1].text, "");
+    }
+
+    #[test]
+    fn links() {
+        let mut text_link = entity("text_link", 0, 4);
+        text_link.url = Some("https://example.com/docs".to_string());
+
+        let formatted = FormattedText::new(
+            "Docs at https://example.com",
+            &[text_link, entity("url", 8, 19)],
+        );
+
+        assert_eq!(
+            formatted.links(),
+            vec!["https://example.com/docs", "https://example.com"]
+        );
+    }
+
+    #[test]
+    fn nested_html() {
+        let mut text_link = entity("text_link", 12, 4);
+        text_link.url = Some("https://example.com/?a=1&b=2".to_string());
+
+        let formatted = FormattedText::new(
+            "Bold italic link <3",
+            &[entity("bold", 0, 11), entity("italic", 5, 6), text_link],
+        );
+
+        assert_eq!(
+            formatted.to_html(),
+            "<b>Bold <i>italic</i></b> <a href=\"https://example.com/?a=1&amp;b=2\">link</a> &lt;3"
+        );
+    }
+
+    #[test]
+    fn escaped_markdown_v2() {
+        let mut pre = entity("pre", 13, 11);
+        pre.language = Some("rust".to_string());
+
+        let formatted =
+            FormattedText::new("Price: 1.50! let a = `b`;", &[entity("bold", 7, 5), pre]);
+
+        assert_eq!(
+            formatted.to_markdown_v2(),
+            "Price: *1\\.50\\!* ```rust\nlet a = \\`b\\`\n```;"
+        );
+    }
+
+    #[test]
+    fn text_builder_html() {
+        let text = TextBuilder::new(ParseMode::Html)
+            .bold("<script>")
+            .text(" & ")
+            .link("docs", "https://example.com/?a=1&b=2")
+            .build();
+
+        assert_eq!(
+            text,
+            "<b>&lt;script&gt;</b> &amp; <a href=\"https://example.com/?a=1&amp;b=2\">docs</a>"
+        );
+    }
+
+    #[test]
+    fn text_builder_markdown_v2() {
+        let text = TextBuilder::new(ParseMode::MarkdownV2)
+            .italic("1+1=2")
+            .text(" ")
+            .code("a `b` c")
+            .text(" ")
+            .link("f(x)", "https://example.com/f(x)")
+            .build();
+
+        assert_eq!(
+            text,
+            "_1\\+1\\=2_ `a \\`b\\` c` [f\\(x\\)](https://example.com/f(x\\))"
+        );
+    }
+
+    #[test]
+    fn text_builder_markdown() {
+        let text = TextBuilder::new(ParseMode::Markdown)
+            .italic("snake_case")
+            .text(" ")
+            .bold("2*2=4")
+            .text(" ")
+            .bold("_a_")
+            .text(" ")
+            .code("a_b")
+            .build();
+
+        assert_eq!(text, "_snake_\\__case_ *2*\\**2=4* \\_*a*\\_ `a_b`");
+    }
+
+    #[test]
+    fn split_plain_paragraphs() {
+        let text = "First paragraph.\n\nSecond one, a bit longer.";
+        let parts = split_text(text, None, 30);
+
+        assert_eq!(
+            parts,
+            vec!["First paragraph.\n", "Second one, a bit longer."]
+        );
+    }
+
+    #[test]
+    fn split_words() {
+        let parts = split_text("one two three four", None, 9);
+
+        assert_eq!(parts, vec!["one two", "three", "four"]);
+    }
+
+    #[test]
+    fn split_html_entities() {
+        let text = "<b>bold <a href=\"https://example.com\">long link</a></b> &amp; more";
+        let parts = split_text(text, Some(ParseMode::Html), 12);
+
+        assert_eq!(
+            parts,
+            vec![
+                "<b>bold <a href=\"https://example.com\">long</a></b>",
+                "<b><a href=\"https://example.com\">link</a></b> &amp; more",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_markdown_v2() {
+        let text = "*bold text* and `some code` \\*not bold\\*";
+        let parts = split_text(text, Some(ParseMode::MarkdownV2), 10);
+
+        assert_eq!(
+            parts,
+            vec!["*bold text*", "and `some`", "`code` \\*not", "bold\\*"]
+        );
+    }
+}
@@ -0,0 +1,22 @@
+use optbuilder::OptionalBuilder;
+use serde_derive::Serialize;
+
+/// The reply for a callback query, which stops the
+/// progress bar of the button and can show a notification.
+#[derive(OptionalBuilder, Default, Debug, Serialize)]
+pub struct AnswerCallbackQuery {
+    pub callback_query_id: String,
+    pub text: Option<String>,
+    pub show_alert: Option<bool>,
+    pub url: Option<String>,
+    pub cache_time: Option<u32>,
+}
+
+impl AnswerCallbackQuery {
+    pub fn new<S: Into<String>>(callback_query_id: S) -> Self {
+        AnswerCallbackQuery {
+            callback_query_id: callback_query_id.into(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde_derive::Serialize;
+
+/// The reply for a pre-checkout query, telegram
+/// expects this answer within 10 seconds.
+#[derive(Debug, Serialize)]
+pub struct AnswerPrecheckoutQuery {
+    pub pre_checkout_query_id: String,
+    pub ok: bool,
+    pub error_message: Option<String>,
+}
+
+impl AnswerPrecheckoutQuery {
+    pub fn ok<S: Into<String>>(pre_checkout_query_id: S) -> Self {
+        AnswerPrecheckoutQuery {
+            pre_checkout_query_id: pre_checkout_query_id.into(),
+            ok: true,
+            error_message: None,
+        }
+    }
+
+    pub fn error<I: Into<String>, M: Into<String>>(
+        pre_checkout_query_id: I,
+        error_message: M,
+    ) -> Self {
+        AnswerPrecheckoutQuery {
+            pre_checkout_query_id: pre_checkout_query_id.into(),
+            ok: false,
+            error_message: Some(error_message.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod answer_precheckout_query_tests {
+    use super::*;
+
+    #[test]
+    fn error_with_owned_id() {
+        let query_id = String::from("query");
+        let answer = AnswerPrecheckoutQuery::error(query_id, "Out of stock");
+
+        assert!(!answer.ok);
+        assert_eq!(answer.pre_checkout_query_id, "query");
+        assert_eq!(answer.error_message.as_deref(), Some("Out of stock"));
+    }
+}
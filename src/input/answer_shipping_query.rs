@@ -0,0 +1,37 @@
+use serde_derive::Serialize;
+
+use crate::input::ShippingOption;
+
+/// The reply for a shipping query, either with the
+/// available shipping options or with an error message
+/// explaining why the order cannot be shipped.
+#[derive(Debug, Serialize)]
+pub struct AnswerShippingQuery {
+    pub shipping_query_id: String,
+    pub ok: bool,
+    pub shipping_options: Option<Vec<ShippingOption>>,
+    pub error_message: Option<String>,
+}
+
+impl AnswerShippingQuery {
+    pub fn ok<S: Into<String>>(
+        shipping_query_id: S,
+        shipping_options: Vec<ShippingOption>,
+    ) -> Self {
+        AnswerShippingQuery {
+            shipping_query_id: shipping_query_id.into(),
+            ok: true,
+            shipping_options: Some(shipping_options),
+            error_message: None,
+        }
+    }
+
+    pub fn error<I: Into<String>, M: Into<String>>(shipping_query_id: I, error_message: M) -> Self {
+        AnswerShippingQuery {
+            shipping_query_id: shipping_query_id.into(),
+            ok: false,
+            shipping_options: None,
+            error_message: Some(error_message.into()),
+        }
+    }
+}
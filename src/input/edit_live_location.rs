@@ -15,7 +15,6 @@ pub struct EditLiveLocation {
     pub message_id: Option<i64>,
     #[optbuilder(skip)]
     pub inline_message_id: Option<String>,
-    #[serde(flatten)]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
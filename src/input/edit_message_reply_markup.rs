@@ -0,0 +1,40 @@
+use serde_derive::Serialize;
+
+use crate::input::ChatID;
+use crate::input::InlineKeyboardMarkup;
+
+/// The EditMessageReplyMarkup replaces the inline keyboard
+/// of a message sent by the bot or of an inline message.
+#[derive(Default, Clone, Debug, Serialize)]
+pub struct EditMessageReplyMarkup {
+    pub chat_id: Option<ChatID>,
+    pub message_id: Option<i64>,
+    pub inline_message_id: Option<String>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+impl EditMessageReplyMarkup {
+    pub fn new<ID: Into<ChatID>>(
+        chat_id: ID,
+        message_id: i64,
+        reply_markup: Option<InlineKeyboardMarkup>,
+    ) -> Self {
+        EditMessageReplyMarkup {
+            chat_id: Some(chat_id.into()),
+            message_id: Some(message_id),
+            reply_markup,
+            ..Default::default()
+        }
+    }
+
+    pub fn inline<S: Into<String>>(
+        inline_message_id: S,
+        reply_markup: Option<InlineKeyboardMarkup>,
+    ) -> Self {
+        EditMessageReplyMarkup {
+            inline_message_id: Some(inline_message_id.into()),
+            reply_markup,
+            ..Default::default()
+        }
+    }
+}
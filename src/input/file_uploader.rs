@@ -1,10 +1,36 @@
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use futures::future::Future;
-use reqwest::r#async::multipart::Part;
+use bytes::{Bytes, BytesMut};
+use futures::{stream, Stream, TryStreamExt};
+use hmac_sha256::Hash;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::multipart::Part;
+use reqwest::Body;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::input::UploaderError;
-use crate::object::FileBuffer;
+use crate::object::{FileBuffer, FileStream};
+use crate::upload_cache::MediaKind;
+
+/// The bytes read from the start of a file to detect its mime type.
+const MIME_HEADER_LENGTH: u64 = 8192;
+/// The size of the chunks a file is uploaded in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The largest photo telegram accepts as a multipart upload.
+pub const PHOTO_UPLOAD_LIMIT: u64 = 10 * 1024 * 1024;
+/// The largest file of other media telegram accepts as a multipart upload.
+pub const FILE_UPLOAD_LIMIT: u64 = 50 * 1024 * 1024;
+/// The largest photo telegram downloads from an url.
+pub const PHOTO_URL_LIMIT: u64 = 5 * 1024 * 1024;
+/// The largest file of other media telegram downloads from an url.
+pub const FILE_URL_LIMIT: u64 = 20 * 1024 * 1024;
+/// The largest thumbnail telegram accepts.
+pub const THUMBNAIL_SIZE_LIMIT: u64 = 200 * 1024;
+/// The largest width and height of a thumbnail.
+pub const THUMBNAIL_DIMENSION_LIMIT: u32 = 320;
 
 /// The FileUploader is an proxy object
 /// for a FileUploader Future:
@@ -13,12 +39,62 @@ use crate::object::FileBuffer;
 /// file that can be uploaded asynchronously, the file
 /// is encoded as a multipart request.
 ///
+/// The mime type is detected from the content of the file,
+/// or from its extension, and can be replaced by with_mime.
+///
 /// It is possible to add a thumbnail to this File.
 #[derive(Debug)]
 pub struct FileUploader {
     pub part: Part,
-    pub thumbnail: Option<Part>,
+    pub thumbnail: Option<Box<FileUploader>>,
     pub file_name: String,
+    pub(crate) source: FileSource,
+    size: Option<u64>,
+    mime: Option<String>,
+    progress: Progress,
+}
+
+/// Where the content of a FileUploader comes from,
+/// kept to identify the file without uploading it.
+#[derive(Clone, Debug)]
+pub(crate) enum FileSource {
+    Path(PathBuf),
+    Bytes(Bytes),
+    /// Streams are read once, by the upload.
+    Stream,
+}
+
+type ProgressCallback = Box<dyn FnMut(u64, Option<u64>) + Send>;
+
+/// The progress callback of a FileUploader, shared with
+/// the stream of its part so it can be set afterwards.
+#[derive(Clone, Default)]
+struct Progress(Arc<Mutex<Option<ProgressCallback>>>);
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "Progress")
+    }
+}
+
+impl Progress {
+    /// Report the bytes of every chunk read from a stream.
+    fn report<S>(self, chunks: S, size: Option<u64>) -> impl Stream<Item = io::Result<Bytes>>
+    where
+        S: Stream<Item = io::Result<Bytes>>,
+    {
+        let mut sent = 0;
+
+        chunks.inspect_ok(move |chunk| {
+            sent += chunk.len() as u64;
+
+            if let Ok(mut progress) = self.0.lock() {
+                if let Some(progress) = progress.as_mut() {
+                    progress(sent, size);
+                }
+            }
+        })
+    }
 }
 
 /// Add a mime type to a FileUploader.
@@ -75,56 +151,428 @@ impl FileUploader {
             .and_then(|name| name.to_str().ok_or(UploaderError::InvalidUTF))
             .map(std::borrow::ToOwned::to_owned);
 
-        let read = tokio::fs::read(file_path);
+        match file_name {
+            Ok(name) => {
+                let size = std::fs::metadata(&file_path).ok().map(|meta| meta.len());
+                let mime = detect_mime(&name, &read_header(&file_path));
 
-        let part = Part::stream(read.into_stream());
+                let chunks = stream::once(tokio::fs::File::open(file_path.clone()))
+                    .map_ok(read_chunks)
+                    .try_flatten();
 
-        match file_name {
-            Ok(name) => Ok(Self {
-                part: part.file_name(name.clone()),
-                thumbnail: None,
-                file_name: name,
-            }),
+                Ok(Self::from_chunks(
+                    name,
+                    chunks,
+                    size,
+                    mime,
+                    FileSource::Path(file_path),
+                ))
+            }
             Err(err) => Err(err),
         }
     }
 
     pub fn from_bytes(name: &str, bytes: Vec<u8>) -> Self {
-        let file_name = name.to_owned();
-        let part = Part::bytes(bytes).file_name(file_name.clone());
-
-        Self {
-            part,
-            thumbnail: None,
-            file_name,
-        }
+        Self::from_source(name.to_owned(), Bytes::from(bytes))
     }
 
     pub fn from_file(file: FileBuffer) -> Self {
         let file_name = file.name.replace("/", "_").to_owned();
-        let part = Part::bytes(file.data).file_name(file_name.clone());
+
+        Self::from_source(file_name, Bytes::from(file.data))
+    }
+
+    /// Create a new FileUploader streaming a file downloaded
+    /// from telegram, without keeping it in memory.
+    pub fn from_file_stream(file: FileStream) -> Self {
+        let file_name = file.name.replace("/", "_");
+        let size = file.size;
+
+        Self::from_stream(&file_name, file, size)
+    }
+
+    /// Create a new FileUploader reading a file from an
+    /// AsyncRead, with the size of the file when known.
+    ///
+    /// The mime type is detected from the name alone.
+    pub fn from_reader<R>(name: &str, reader: R, size: Option<u64>) -> Self
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        Self::from_stream(name, read_chunks(Box::pin(reader)), size)
+    }
+
+    /// Create a new FileUploader from a stream of chunks,
+    /// with the size of the file when known.
+    ///
+    /// The mime type is detected from the name alone.
+    pub fn from_stream<S, E>(name: &str, chunks: S, size: Option<u64>) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        Self::from_chunks(
+            name.to_owned(),
+            chunks.map_err(io::Error::other),
+            size,
+            mime_guess::from_path(name).first_raw(),
+            FileSource::Stream,
+        )
+    }
+
+    fn from_source(file_name: String, data: Bytes) -> Self {
+        let length = data.len();
+        let mime = detect_mime(&file_name, &data);
+
+        let chunk_data = data.clone();
+        let chunks = stream::iter(
+            (0..length)
+                .step_by(CHUNK_SIZE)
+                .map(move |start| Ok(chunk_data.slice(start..length.min(start + CHUNK_SIZE)))),
+        );
+
+        Self::from_chunks(
+            file_name,
+            chunks,
+            Some(length as u64),
+            mime,
+            FileSource::Bytes(data),
+        )
+    }
+
+    fn from_chunks<S>(
+        file_name: String,
+        chunks: S,
+        size: Option<u64>,
+        mime: Option<&str>,
+        source: FileSource,
+    ) -> Self
+    where
+        S: Stream<Item = io::Result<Bytes>> + Send + 'static,
+    {
+        let progress = Progress::default();
+        let body = Body::wrap_stream(progress.clone().report(chunks, size));
+
+        let part = match size {
+            Some(size) => Part::stream_with_length(body, size),
+            None => Part::stream(body),
+        };
 
         Self {
-            part,
+            part: with_content_type(part.file_name(file_name.clone()), mime),
             thumbnail: None,
             file_name,
+            source,
+            size,
+            mime: mime.map(ToOwned::to_owned),
+            progress,
         }
     }
 
+    /// The SHA-256 of the content of the file, files on disk are
+    /// read again to compute it while streams cannot be hashed.
+    pub async fn content_hash(&self) -> io::Result<Option<[u8; 32]>> {
+        match &self.source {
+            FileSource::Bytes(data) => Ok(Some(Hash::hash(data))),
+            FileSource::Path(path) => {
+                let mut file = tokio::fs::File::open(path).await?;
+                let mut buffer = vec![0; CHUNK_SIZE];
+                let mut hash = Hash::new();
+
+                loop {
+                    match file.read(&mut buffer).await? {
+                        0 => return Ok(Some(hash.finalize())),
+                        read => hash.update(&buffer[..read]),
+                    }
+                }
+            }
+            FileSource::Stream => Ok(None),
+        }
+    }
+
+    /// The size of the file in bytes, when it can be read.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// The mime type of the file, when it is known.
+    pub fn mime(&self) -> Option<&str> {
+        self.mime.as_deref()
+    }
+
     /// Try to add a mime type to the FileUploader.
     pub fn with_mime(mut self, mime_string: &str) -> Result<Self, UploaderError> {
+        // Clear the detected content type, so it is not sent twice.
         self.part = self
             .part
+            .headers(HeaderMap::new())
             .mime_str(mime_string)
             .map_err(UploaderError::WrongMime)?;
+        self.mime = Some(mime_string.to_owned());
 
         Ok(self)
     }
 
+    /// Call the closure on every chunk uploaded with the bytes
+    /// sent so far and the size of the file, when known.
+    pub fn with_progress<F>(self, progress: F) -> Self
+    where
+        F: FnMut(u64, Option<u64>) + Send + 'static,
+    {
+        if let Ok(mut callback) = self.progress.0.lock() {
+            *callback = Some(Box::new(progress));
+        }
+
+        self
+    }
+
     /// Add an thumbnail to the FileUploader.
     pub fn with_thumbnail(mut self, uploader: FileUploader) -> Self {
-        self.thumbnail = Some(uploader.part);
+        self.thumbnail = Some(Box::new(uploader));
 
         self
     }
+
+    /// Check the file and its thumbnail are within
+    /// the telegram limits for a kind of media.
+    pub async fn validate(&self, kind: MediaKind) -> Result<(), UploaderError> {
+        let limit = kind.upload_limit();
+
+        match self.size {
+            Some(size) if size > limit => Err(UploaderError::FileTooLarge(
+                kind.field().to_owned(),
+                size,
+                limit,
+            )),
+            _ => match &self.thumbnail {
+                Some(thumbnail) => thumbnail.validate_thumbnail().await,
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Check the file is a JPEG small enough to be a thumbnail.
+    pub(crate) async fn validate_thumbnail(&self) -> Result<(), UploaderError> {
+        match self.mime() {
+            Some("image/jpeg") | Some("image/jpg") => (),
+            mime => {
+                return Err(UploaderError::InvalidThumbnail(format!(
+                    "Thumbnails must be JPEG, found {}.",
+                    mime.unwrap_or("an unknown type")
+                )))
+            }
+        }
+
+        if let Some(size) = self.size.filter(|size| *size > THUMBNAIL_SIZE_LIMIT) {
+            return Err(UploaderError::InvalidThumbnail(format!(
+                "Thumbnails must have at most {} bytes, found {}.",
+                THUMBNAIL_SIZE_LIMIT, size
+            )));
+        }
+
+        let content = match &self.source {
+            FileSource::Bytes(data) => Some(data.to_vec()),
+            FileSource::Path(path) => tokio::fs::read(path).await.ok(),
+            FileSource::Stream => None,
+        };
+
+        match content.as_deref().and_then(jpeg_dimensions) {
+            Some((width, height))
+                if width > THUMBNAIL_DIMENSION_LIMIT || height > THUMBNAIL_DIMENSION_LIMIT =>
+            {
+                Err(UploaderError::InvalidThumbnail(format!(
+                    "Thumbnails must be at most {}px wide and tall, found {}x{}.",
+                    THUMBNAIL_DIMENSION_LIMIT, width, height
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The width and height of a JPEG, read
+/// from its first start of frame segment.
+fn jpeg_dimensions(content: &[u8]) -> Option<(u32, u32)> {
+    let mut position = 2;
+
+    while position + 9 <= content.len() {
+        if content[position] != 0xFF {
+            return None;
+        }
+
+        let marker = content[position + 1];
+        let length = u16::from_be_bytes([content[position + 2], content[position + 3]]) as usize;
+
+        // Start of frame markers, except DHT, JPG and DAC.
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            let height = u16::from_be_bytes([content[position + 5], content[position + 6]]);
+            let width = u16::from_be_bytes([content[position + 7], content[position + 8]]);
+
+            return Some((width.into(), height.into()));
+        }
+
+        position += 2 + length;
+    }
+
+    None
+}
+
+/// Detect the mime type of a file from the magic
+/// bytes of its content, or the extension of its name.
+fn detect_mime(file_name: &str, content: &[u8]) -> Option<&'static str> {
+    infer::get(content)
+        .map(|kind| kind.mime_type())
+        .or_else(|| mime_guess::from_path(file_name).first_raw())
+}
+
+/// Read the start of a file, errors are left
+/// for the upload to report.
+fn read_header(path: &Path) -> Vec<u8> {
+    let mut header = Vec::new();
+
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(MIME_HEADER_LENGTH).read_to_end(&mut header);
+    }
+
+    header
+}
+
+/// Read a reader chunk by chunk, until its end or an error.
+fn read_chunks<R>(reader: R) -> impl Stream<Item = io::Result<Bytes>>
+where
+    R: AsyncRead + Unpin,
+{
+    stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        let mut chunk = BytesMut::with_capacity(CHUNK_SIZE);
+
+        match reader.read_buf(&mut chunk).await {
+            Ok(0) => None,
+            Ok(_) => Some((Ok(chunk.freeze()), Some(reader))),
+            Err(err) => Some((Err(err), None)),
+        }
+    })
+}
+
+/// Set the content type of a part as a header, so a part
+/// is not lost on the error of an unexpected mime type.
+fn with_content_type(part: Part, mime: Option<&str>) -> Part {
+    match mime.and_then(|mime| HeaderValue::from_str(mime).ok()) {
+        Some(content_type) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, content_type);
+
+            part.headers(headers)
+        }
+        None => part,
+    }
+}
+
+#[cfg(test)]
+mod file_uploader_tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn detect_mimes() {
+        let puppy = std::fs::read("res/puppy.jpg").expect("Missing file");
+
+        assert_eq!(detect_mime("puppy", &puppy), Some("image/jpeg"));
+        assert_eq!(detect_mime("puppy.png", &puppy), Some("image/jpeg"));
+        assert_eq!(detect_mime("notes.txt", b"beep bop"), Some("text/plain"));
+        assert_eq!(detect_mime("notes", b"beep bop"), None);
+    }
+
+    #[test]
+    fn validate_sizes() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let large = FileUploader::from_bytes("large.jpg", vec![0; 11 * 1024 * 1024]);
+
+        match runtime.block_on(large.validate(MediaKind::Photo)) {
+            Err(UploaderError::FileTooLarge(field, size, limit)) => {
+                assert_eq!(field, "photo");
+                assert_eq!(size, 11 * 1024 * 1024);
+                assert_eq!(limit, PHOTO_UPLOAD_LIMIT);
+            }
+            other => panic!("Expected FileTooLarge, found {:?}", other),
+        }
+
+        assert!(runtime
+            .block_on(large.validate(MediaKind::Document))
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_thumbnails() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let thumbnail = |path| {
+            let document = FileUploader::new("res/some_text").unwrap();
+
+            document.with_thumbnail(FileUploader::new(path).unwrap())
+        };
+
+        let puppy = thumbnail("res/puppy.jpg");
+        assert!(runtime
+            .block_on(puppy.validate(MediaKind::Document))
+            .is_ok());
+
+        let png = thumbnail("res/brownpuppy.png");
+        match runtime.block_on(png.validate(MediaKind::Document)) {
+            Err(UploaderError::InvalidThumbnail(_)) => (),
+            other => panic!("Expected InvalidThumbnail, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_reader_chunks() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let content = vec![7; CHUNK_SIZE + 16];
+
+        let chunks: Vec<Bytes> = runtime
+            .block_on(read_chunks(std::io::Cursor::new(content.clone())).try_collect())
+            .expect("Cannot read the chunks");
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn report_progress() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress_reports = reports.clone();
+
+        let chunks = stream::empty::<io::Result<Bytes>>();
+        let uploader = FileUploader::from_stream("beep.txt", chunks, Some(8))
+            .with_progress(move |sent, size| progress_reports.lock().unwrap().push((sent, size)));
+
+        let stream = uploader.progress.clone().report(
+            stream::iter(vec![Ok(Bytes::from("beep ")), Ok(Bytes::from("bop"))]),
+            uploader.size(),
+        );
+        let _: Vec<Bytes> = runtime.block_on(stream.try_collect()).unwrap();
+
+        assert_eq!(uploader.mime(), Some("text/plain"));
+        assert_eq!(*reports.lock().unwrap(), vec![(5, Some(8)), (8, Some(8))]);
+        assert!(runtime.block_on(uploader.content_hash()).unwrap().is_none());
+    }
+
+    #[test]
+    fn read_jpeg_dimensions() {
+        let puppy = std::fs::read("res/puppy.jpg").expect("Missing file");
+
+        assert_eq!(jpeg_dimensions(&puppy), Some((200, 200)));
+        assert_eq!(jpeg_dimensions(b"beep bop"), None);
+    }
+
+    #[test]
+    fn read_headers() {
+        let header = read_header(Path::new("res/puppy.jpg"));
+
+        let size = std::fs::metadata("res/puppy.jpg").unwrap().len();
+
+        assert_eq!(header.len() as u64, size.min(MIME_HEADER_LENGTH));
+        assert_eq!(detect_mime("puppy", &header), Some("image/jpeg"));
+        assert!(read_header(Path::new("res/missing.jpg")).is_empty());
+    }
 }
@@ -0,0 +1,224 @@
+use crate::error::BotError;
+use crate::input::{InlineKeyboardButton, InlineKeyboardButtonMessage, InlineKeyboardMarkup};
+
+/// The maximum number of buttons on a row of an inline keyboard.
+pub const INLINE_KEYBOARD_ROW_LIMIT: usize = 8;
+/// The maximum number of buttons on an inline keyboard.
+pub const INLINE_KEYBOARD_LIMIT: usize = 100;
+/// The maximum size in bytes of a callback data.
+pub const CALLBACK_DATA_LIMIT: usize = 64;
+
+/// A row of buttons being built by an InlineKeyboard.
+#[derive(Clone, Debug, Default)]
+pub struct InlineRow {
+    buttons: Vec<InlineKeyboardButton>,
+}
+
+impl InlineRow {
+    fn button<S: Into<String>>(mut self, text: S, message: InlineKeyboardButtonMessage) -> Self {
+        self.buttons.push(InlineKeyboardButton {
+            text: text.into(),
+            inline_message: message,
+        });
+
+        self
+    }
+
+    /// Add a button sending a callback query with data.
+    pub fn callback<S: Into<String>, D: Into<String>>(self, text: S, data: D) -> Self {
+        self.button(text, InlineKeyboardButtonMessage::CallbackData(data.into()))
+    }
+
+    /// Add a button opening an url.
+    pub fn url<S: Into<String>, U: Into<String>>(self, text: S, url: U) -> Self {
+        self.button(text, InlineKeyboardButtonMessage::Url(url.into()))
+    }
+
+    /// Add a button asking the user to choose a chat
+    /// and starting an inline query with the bot there.
+    pub fn switch_inline_query<S: Into<String>, Q: Into<String>>(self, text: S, query: Q) -> Self {
+        self.button(
+            text,
+            InlineKeyboardButtonMessage::SwitchInlineQuery(query.into()),
+        )
+    }
+
+    /// Add a button starting an inline query with the bot on the current chat.
+    pub fn switch_inline_query_current_chat<S: Into<String>, Q: Into<String>>(
+        self,
+        text: S,
+        query: Q,
+    ) -> Self {
+        self.button(
+            text,
+            InlineKeyboardButtonMessage::SwitchInlineQueryCurrentChat(query.into()),
+        )
+    }
+
+    /// Add a pay button, it must be the first button of
+    /// the first row and can only be sent with invoices.
+    pub fn pay<S: Into<String>>(self, text: S) -> Self {
+        self.button(text, InlineKeyboardButtonMessage::Pay(true))
+    }
+}
+
+/// The InlineKeyboard is a builder for InlineKeyboardMarkup.
+///
+/// ```
+/// use beepbop::input::{InlineKeyboard, ReplyMarkup};
+///
+/// let markup: ReplyMarkup = InlineKeyboard::new()
+///     .row(|r| r.callback("Yes", "y").callback("No", "n"))
+///     .row(|r| r.url("Docs", "https://core.telegram.org/bots/api"))
+///     .finish()
+///     .unwrap()
+///     .into();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InlineKeyboard {
+    rows: Vec<Vec<InlineKeyboardButton>>,
+    wrap: Option<usize>,
+}
+
+impl InlineKeyboard {
+    pub fn new() -> Self {
+        InlineKeyboard::default()
+    }
+
+    /// Wrap the rows added after this call so
+    /// they have at most `buttons` buttons each.
+    pub fn wrap(mut self, buttons: usize) -> Self {
+        self.wrap = Some(buttons.max(1));
+
+        self
+    }
+
+    /// Add a row of buttons, which can span
+    /// many rows if the keyboard is wrapped.
+    pub fn row<F>(mut self, build: F) -> Self
+    where
+        F: FnOnce(InlineRow) -> InlineRow,
+    {
+        let buttons = build(InlineRow::default()).buttons;
+
+        match self.wrap {
+            Some(wrap) => self
+                .rows
+                .extend(buttons.chunks(wrap).map(|chunk| chunk.to_vec())),
+            None if !buttons.is_empty() => self.rows.push(buttons),
+            None => (),
+        }
+
+        self
+    }
+
+    /// Finish the building of the InlineKeyboardMarkup,
+    /// checking it against the Telegram limits.
+    pub fn finish(self) -> Result<InlineKeyboardMarkup, BotError> {
+        let number_of_buttons: usize = self.rows.iter().map(Vec::len).sum();
+
+        if number_of_buttons > INLINE_KEYBOARD_LIMIT {
+            return Err(BotError::InvalidKeyboard(format!(
+                "Inline keyboards can have at most {} buttons, found {}",
+                INLINE_KEYBOARD_LIMIT, number_of_buttons
+            )));
+        }
+
+        for (index, row) in self.rows.iter().enumerate() {
+            if row.len() > INLINE_KEYBOARD_ROW_LIMIT {
+                return Err(BotError::InvalidKeyboard(format!(
+                    "Inline keyboard rows can have at most {} buttons, row {} has {}",
+                    INLINE_KEYBOARD_ROW_LIMIT,
+                    index,
+                    row.len()
+                )));
+            }
+
+            for button in row {
+                check_button(button)?;
+            }
+        }
+
+        Ok(InlineKeyboardMarkup {
+            inline_keyboard: self.rows,
+        })
+    }
+}
+
+fn check_button(button: &InlineKeyboardButton) -> Result<(), BotError> {
+    if button.text.is_empty() {
+        return Err(BotError::InvalidKeyboard(
+            "Inline keyboard buttons must have a text".to_string(),
+        ));
+    }
+
+    match &button.inline_message {
+        InlineKeyboardButtonMessage::CallbackData(data)
+            if data.is_empty() || data.len() > CALLBACK_DATA_LIMIT =>
+        {
+            Err(BotError::InvalidKeyboard(format!(
+                "Callback data must have between 1 and {} bytes, found {} on button {}",
+                CALLBACK_DATA_LIMIT,
+                data.len(),
+                button.text
+            )))
+        }
+        InlineKeyboardButtonMessage::Url(url) if url.is_empty() => Err(BotError::InvalidKeyboard(
+            format!("Url of button {} is empty", button.text),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod inline_keyboard_tests {
+    use super::*;
+
+    #[test]
+    fn wrap_rows() {
+        let markup = InlineKeyboard::new()
+            .wrap(2)
+            .row(|r| (1..=5).fold(r, |r, n| r.callback(n.to_string(), n.to_string())))
+            .finish()
+            .expect("Invalid keyboard");
+
+        let lengths: Vec<usize> = markup.inline_keyboard.iter().map(Vec::len).collect();
+        assert_eq!(lengths, vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn serialize_buttons() {
+        let markup = InlineKeyboard::new()
+            .row(|r| r.callback("Yes", "y").url("Docs", "https://example.com"))
+            .finish()
+            .expect("Invalid keyboard");
+
+        assert_eq!(
+            serde_json::to_value(&markup).unwrap(),
+            serde_json::json!({"inline_keyboard": [[
+                {"text": "Yes", "callback_data": "y"},
+                {"text": "Docs", "url": "https://example.com"},
+            ]]})
+        );
+    }
+
+    #[test]
+    fn telegram_limits() {
+        let long_row = InlineKeyboard::new()
+            .row(|r| (0..9).fold(r, |r, n| r.callback("Button", n.to_string())))
+            .finish();
+        assert!(long_row.is_err());
+
+        let long_data = InlineKeyboard::new()
+            .row(|r| r.callback("Button", "x".repeat(65)))
+            .finish();
+        assert!(long_data.is_err());
+
+        let too_many = (0..101)
+            .fold(InlineKeyboard::new(), |k, n| {
+                k.row(|r| r.callback("Button", n.to_string()))
+            })
+            .finish();
+        assert!(too_many.is_err());
+    }
+}
@@ -1,8 +1,8 @@
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::input::InlineKeyboardButtonMessage;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InlineKeyboardButton {
     pub text: String,
     #[serde(flatten)]
@@ -1,6 +1,6 @@
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum InlineKeyboardButtonMessage {
     #[serde(rename = "url")]
     Url(String),
@@ -12,4 +12,8 @@ pub enum InlineKeyboardButtonMessage {
     SwitchInlineQueryCurrentChat(String),
     #[serde(rename = "pay")]
     Pay(bool),
+    #[serde(rename = "login_url")]
+    LoginUrl(serde_json::Value),
+    #[serde(rename = "callback_game")]
+    CallbackGame(serde_json::Value),
 }
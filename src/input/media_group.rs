@@ -1,12 +1,18 @@
 use std::error::Error;
 
 use optbuilder::OptionalBuilder;
+use reqwest::Client;
 use serde_derive::Serialize;
+use serde_json::{Map, Value};
 
 use crate::error::BotError;
+use crate::formatting::check_caption;
 use crate::input::ChatID;
 use crate::input::FileUploader;
+use crate::input::ParseMode;
 use crate::input::Uploader;
+use crate::input::UploaderError;
+use crate::upload_cache::MediaKind;
 
 #[derive(OptionalBuilder, Default, Debug, Serialize)]
 /// The MediaVideo is a struct containing metadata
@@ -21,7 +27,7 @@ pub struct MediaVideo {
     #[serde(skip_serializing_if = "Option::is_none")]
     caption: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    parse_mode: Option<String>,
+    parse_mode: Option<ParseMode>,
     #[serde(skip_serializing_if = "Option::is_none")]
     width: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,19 +48,111 @@ pub struct MediaPhoto {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+}
+
+#[derive(OptionalBuilder, Default, Debug, Serialize)]
+/// The MediaDocument is a struct containing metadata
+/// about a document used within an MediaGroup. the media
+/// String is a name granted by the MediaGroup object.
+pub struct MediaDocument {
+    r#type: String,
+    media: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[optbuilder(skip)]
+    thumb: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<ParseMode>,
+}
+
+#[derive(OptionalBuilder, Default, Debug, Serialize)]
+/// The MediaAudio is a struct containing metadata
+/// about an audio used within an MediaGroup. the media
+/// String is a name granted by the MediaGroup object.
+pub struct MediaAudio {
+    r#type: String,
+    media: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[optbuilder(skip)]
+    thumb: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<ParseMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    performer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 /// The MediaEntry Enum is a container type
-/// for both MediaVideo and MediaPhoto.
+/// for every media of a MediaGroup.
 ///
 /// This is a detail implementation of MediaGroup
 /// and probably should not be used directly.
 enum MediaEntry {
     Video(MediaVideo),
     Photo(MediaPhoto),
+    Document(MediaDocument),
+    Audio(MediaAudio),
+}
+
+impl MediaEntry {
+    fn kind(&self) -> MediaKind {
+        match self {
+            MediaEntry::Video(_) => MediaKind::Video,
+            MediaEntry::Photo(_) => MediaKind::Photo,
+            MediaEntry::Document(_) => MediaKind::Document,
+            MediaEntry::Audio(_) => MediaKind::Audio,
+        }
+    }
+
+    fn caption(&self) -> (Option<&String>, Option<ParseMode>) {
+        match self {
+            MediaEntry::Video(video) => (video.caption.as_ref(), video.parse_mode),
+            MediaEntry::Photo(photo) => (photo.caption.as_ref(), photo.parse_mode),
+            MediaEntry::Document(document) => (document.caption.as_ref(), document.parse_mode),
+            MediaEntry::Audio(audio) => (audio.caption.as_ref(), audio.parse_mode),
+        }
+    }
+}
+
+/// The media a kind can be grouped with, telegram
+/// only mixes photos with videos on a group.
+fn album_of(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Document => "documents",
+        MediaKind::Audio => "audios",
+        _ => "photos and videos",
+    }
+}
+
+#[derive(Debug)]
+/// The MediaItem is an entry of a MediaGroup
+/// with the files it uploads.
+struct MediaItem {
+    /// The position of the item on the builder,
+    /// giving unique names to its attachments.
+    index: usize,
+    entry: MediaEntry,
+    uploader: Uploader,
+    thumbnail: Option<FileUploader>,
+}
+
+/// The attachment name of the media file of an item.
+fn media_name(index: usize) -> String {
+    format!("media{}", index)
+}
+
+/// The attachment name of the thumbnail of an item.
+fn thumbnail_name(index: usize) -> String {
+    format!("thumb{}", index)
 }
 
 #[derive(Default, Debug, Serialize)]
@@ -82,6 +180,20 @@ pub struct MediaGroupQuery {
 pub struct Attachment {
     pub uploader: Uploader,
     pub name: String,
+    /// The kind of media of the entry, None for thumbnails.
+    pub(crate) kind: Option<MediaKind>,
+}
+
+impl Attachment {
+    /// Check the attachment is within the telegram limits
+    /// of the media of its entry, or of thumbnails.
+    pub(crate) async fn validate(&self, client: &Client) -> Result<(), UploaderError> {
+        match (&self.uploader, self.kind) {
+            (uploader, Some(kind)) => uploader.validate(kind, client).await,
+            (Uploader::File(thumbnail), None) => thumbnail.validate_thumbnail().await,
+            (_, None) => Ok(()),
+        }
+    }
 }
 
 #[derive(Default, Debug)]
@@ -97,10 +209,28 @@ pub struct MediaGroup {
 #[derive(OptionalBuilder, Default, Debug)]
 pub struct MediaGroupBuilder {
     chat_id: ChatID,
-    media: Vec<MediaEntry>,
+    media: Vec<MediaItem>,
     disable_notification: Option<bool>,
     reply_to_message_id: Option<i64>,
-    attachments: Vec<Attachment>,
+}
+
+/// A part of an album sent by Bot::send_media_album,
+/// a single media is sent by itself.
+#[derive(Debug)]
+pub(crate) enum AlbumPart {
+    Group(MediaGroup),
+    Single(Box<SingleMedia>),
+}
+
+/// A media sent out of a group, with the
+/// request fields of its kind of media.
+#[derive(Debug)]
+pub(crate) struct SingleMedia {
+    pub(crate) kind: MediaKind,
+    pub(crate) query: Map<String, Value>,
+    pub(crate) uploader: Uploader,
+    /// The thumbnail of a media not uploaded as a file.
+    pub(crate) thumbnail: Option<FileUploader>,
 }
 
 impl MediaGroup {
@@ -125,7 +255,52 @@ impl MediaGroupBuilder {
             )));
         }
 
-        match serde_json::to_string(&self.media) {
+        let first_album = album_of(self.media[0].entry.kind());
+
+        if let Some(item) = self
+            .media
+            .iter()
+            .find(|item| album_of(item.entry.kind()) != first_album)
+        {
+            return Err(BotError::InvalidMediaGroup(format!(
+                "Group media cannot mix {} with {}",
+                first_album,
+                album_of(item.entry.kind())
+            )));
+        }
+
+        for item in &self.media {
+            if let (Some(caption), parse_mode) = item.entry.caption() {
+                check_caption(caption, parse_mode)?;
+            }
+        }
+
+        let mut entries = Vec::with_capacity(number_of_medias);
+        let mut attachments = Vec::new();
+
+        // Attachments are named by position, the parts
+        // keep the file names of the files.
+        for item in self.media {
+            if let Some(thumbnail) = item.thumbnail {
+                attachments.push(Attachment {
+                    name: thumbnail_name(item.index),
+                    uploader: thumbnail.into(),
+                    kind: None,
+                });
+            }
+
+            if let Uploader::File(file_uploader) = item.uploader {
+                attachments.push(Attachment {
+                    name: media_name(item.index),
+                    uploader: file_uploader.into(),
+                    kind: Some(item.entry.kind()),
+                });
+            }
+
+            entries.push(item.entry);
+        }
+
+        match serde_json::to_string(&entries) {
             Ok(media_encoded) => Ok(MediaGroup {
                 media_encoded,
                 query: MediaGroupQuery {
@@ -133,7 +308,7 @@ impl MediaGroupBuilder {
                     disable_notification: self.disable_notification,
                     reply_to_message_id: self.reply_to_message_id,
                 },
-                attachments: self.attachments,
+                attachments,
             }),
             Err(err) => Err(BotError::InvalidMediaGroup(format!(
                 "Cannot deserialize media group: {}",
@@ -142,6 +317,122 @@ impl MediaGroupBuilder {
         }
     }
 
+    /// Split the media in order into groups telegram accepts,
+    /// of up to 10 media that can be mixed, sending lone
+    /// media by themselves.
+    ///
+    /// Only the first part replies to a message.
+    pub(crate) fn split_album(self) -> Result<Vec<AlbumPart>, BotError> {
+        let MediaGroupBuilder {
+            chat_id,
+            media,
+            disable_notification,
+            reply_to_message_id,
+        } = self;
+
+        let mut groups: Vec<Vec<MediaItem>> = Vec::new();
+
+        for item in media {
+            match groups.last_mut() {
+                Some(group)
+                    if group.len() < 10
+                        && album_of(group[0].entry.kind()) == album_of(item.entry.kind()) =>
+                {
+                    group.push(item)
+                }
+                _ => groups.push(vec![item]),
+            }
+        }
+
+        groups
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut media)| {
+                let reply_to_message_id = reply_to_message_id.filter(|_| index == 0);
+
+                if media.len() > 1 {
+                    let group = MediaGroupBuilder {
+                        chat_id: chat_id.clone(),
+                        media,
+                        disable_notification,
+                        reply_to_message_id,
+                    };
+
+                    return group.finish().map(AlbumPart::Group);
+                }
+
+                let item = media.remove(0);
+                let kind = item.entry.kind();
+
+                let mut query = match serde_json::to_value(&item.entry) {
+                    Ok(Value::Object(fields)) => fields,
+                    _ => {
+                        return Err(BotError::InvalidMediaGroup(
+                            "Cannot encode a media of the group".to_string(),
+                        ))
+                    }
+                };
+
+                for field in &["type", "media", "thumb"] {
+                    query.remove(*field);
+                }
+
+                query.insert("chat_id".into(), serde_json::json!(chat_id));
+                query.insert(
+                    "disable_notification".into(),
+                    serde_json::json!(disable_notification),
+                );
+                query.insert(
+                    "reply_to_message_id".into(),
+                    serde_json::json!(reply_to_message_id),
+                );
+
+                let (uploader, thumbnail) = match (item.uploader, item.thumbnail) {
+                    (Uploader::File(file_uploader), Some(thumbnail)) => {
+                        (file_uploader.with_thumbnail(thumbnail).into(), None)
+                    }
+                    (uploader, thumbnail) => (uploader, thumbnail),
+                };
+
+                Ok(AlbumPart::Single(Box::new(SingleMedia {
+                    kind,
+                    query,
+                    uploader,
+                    thumbnail,
+                })))
+            })
+            .collect()
+    }
+
+    /// Add a media to the group, the entry is created
+    /// from the media and thumbnail names.
+    fn add_media<F>(mut self, uploader: Uploader, thumbnail: Option<FileUploader>, entry: F) -> Self
+    where
+        F: FnOnce(String, Option<String>) -> MediaEntry,
+    {
+        let index = self.media.len();
+
+        let media = match &uploader {
+            Uploader::File(_) => format!("attach://{}", media_name(index)),
+            Uploader::Id(id) => id.0.clone(),
+            Uploader::Url(url) => url.0.clone(),
+            Uploader::Empty => return self,
+        };
+
+        let thumb = thumbnail
+            .as_ref()
+            .map(|_| format!("attach://{}", thumbnail_name(index)));
+
+        self.media.push(MediaItem {
+            index,
+            entry: entry(media, thumb),
+            uploader,
+            thumbnail,
+        });
+
+        self
+    }
+
     /// Add a new photo to the MediaGroup, the photo
     /// will be created from the file provided.
     pub fn add_photo<U>(self, uploader: U) -> Self
@@ -161,55 +452,44 @@ impl MediaGroupBuilder {
         self.add_video_with(uploader, thumbnail, std::convert::identity)
     }
 
+    /// Add a new document to the MediaGroup, documents
+    /// can only be grouped with other documents.
+    pub fn add_document<U>(self, uploader: U, thumbnail: Option<FileUploader>) -> Self
+    where
+        U: Into<Uploader>,
+    {
+        self.add_document_with(uploader, thumbnail, std::convert::identity)
+    }
+
+    /// Add a new audio to the MediaGroup, audios
+    /// can only be grouped with other audios.
+    pub fn add_audio<U>(self, uploader: U, thumbnail: Option<FileUploader>) -> Self
+    where
+        U: Into<Uploader>,
+    {
+        self.add_audio_with(uploader, thumbnail, std::convert::identity)
+    }
+
     /// Add a new photo to the MediaGroup but with an edit_function
     /// to edit some details of the photo after its creation.
-    pub fn add_photo_with<U, F>(mut self, uploader: U, edit_function: F) -> Self
+    pub fn add_photo_with<U, F>(self, uploader: U, edit_function: F) -> Self
     where
         U: Into<Uploader>,
         F: Fn(MediaPhoto) -> MediaPhoto,
     {
-        match uploader.into() {
-            Uploader::File(file_uploader) => {
-                let photo = MediaPhoto {
-                    r#type: "photo".into(),
-                    media: format!("attach://{}", file_uploader.file_name.clone()),
-                    ..Default::default()
-                };
-
-                self.media.push(MediaEntry::Photo(edit_function(photo)));
-                self.attachments.push(Attachment {
-                    name: file_uploader.file_name.clone(),
-                    uploader: file_uploader.into(),
-                });
-            }
-            Uploader::Id(id) => {
-                let photo = MediaPhoto {
-                    r#type: "photo".into(),
-                    media: id.0,
-                    ..Default::default()
-                };
-
-                self.media.push(MediaEntry::Photo(edit_function(photo)));
-            }
-            Uploader::Url(url) => {
-                let photo = MediaPhoto {
-                    r#type: "photo".into(),
-                    media: url.0,
-                    ..Default::default()
-                };
-
-                self.media.push(MediaEntry::Photo(edit_function(photo)));
-            }
-            Uploader::Empty => (),
-        }
-
-        self
+        self.add_media(uploader.into(), None, |media, _| {
+            MediaEntry::Photo(edit_function(MediaPhoto {
+                r#type: "photo".into(),
+                media,
+                ..Default::default()
+            }))
+        })
     }
 
     /// Add a new video to the MediaGroup, but with an edit_function
     /// to edit some details of the photo after its creation.
     pub fn add_video_with<U, F>(
-        mut self,
+        self,
         uploader: U,
         thumbnail: Option<FileUploader>,
         edit_function: F,
@@ -218,53 +498,194 @@ impl MediaGroupBuilder {
         U: Into<Uploader>,
         F: Fn(MediaVideo) -> MediaVideo,
     {
-        match uploader.into() {
-            Uploader::File(file_uploader) => {
-                let mut video = MediaVideo {
-                    r#type: "video".into(),
-                    media: format!("attach://{}", file_uploader.file_name.clone()),
-                    ..Default::default()
-                };
+        // with_thumb method is not created to avoid
+        // the user from using it inside the build edit_function closure.
+        self.add_media(uploader.into(), thumbnail, |media, thumb| {
+            MediaEntry::Video(edit_function(MediaVideo {
+                r#type: "video".into(),
+                media,
+                thumb,
+                ..Default::default()
+            }))
+        })
+    }
 
-                if let Some(thumb) = thumbnail {
-                    // with_thumb method is not created to avoid
-                    // the user from using it inside the build edit_function closure.
-                    video.thumb = Some(format!("attach://{}", thumb.file_name));
+    /// Add a new document to the MediaGroup, but with an edit_function
+    /// to edit some details of the document after its creation.
+    pub fn add_document_with<U, F>(
+        self,
+        uploader: U,
+        thumbnail: Option<FileUploader>,
+        edit_function: F,
+    ) -> Self
+    where
+        U: Into<Uploader>,
+        F: Fn(MediaDocument) -> MediaDocument,
+    {
+        self.add_media(uploader.into(), thumbnail, |media, thumb| {
+            MediaEntry::Document(edit_function(MediaDocument {
+                r#type: "document".into(),
+                media,
+                thumb,
+                ..Default::default()
+            }))
+        })
+    }
 
-                    self.attachments.push(Attachment {
-                        name: thumb.file_name.clone(),
-                        uploader: thumb.into(),
-                    });
-                };
+    /// Add a new audio to the MediaGroup, but with an edit_function
+    /// to edit some details of the audio after its creation.
+    pub fn add_audio_with<U, F>(
+        self,
+        uploader: U,
+        thumbnail: Option<FileUploader>,
+        edit_function: F,
+    ) -> Self
+    where
+        U: Into<Uploader>,
+        F: Fn(MediaAudio) -> MediaAudio,
+    {
+        self.add_media(uploader.into(), thumbnail, |media, thumb| {
+            MediaEntry::Audio(edit_function(MediaAudio {
+                r#type: "audio".into(),
+                media,
+                thumb,
+                ..Default::default()
+            }))
+        })
+    }
+}
 
-                self.media.push(MediaEntry::Video(edit_function(video)));
+#[cfg(test)]
+mod media_group_tests {
+    use super::*;
+    use crate::input::{file_id, file_url};
 
-                self.attachments.push(Attachment {
-                    name: file_uploader.file_name.clone(),
-                    uploader: file_uploader.into(),
-                });
-            }
-            Uploader::Id(id) => {
-                let video = MediaVideo {
-                    r#type: "video".into(),
-                    media: id.0.clone(),
-                    ..Default::default()
-                };
+    fn photos(count: usize) -> MediaGroupBuilder {
+        (0..count).fold(MediaGroup::build(1234), |group, index| {
+            group.add_photo(file_id(format!("photo_{}", index)))
+        })
+    }
+
+    #[test]
+    fn mix_media() {
+        let mixed = photos(1).add_video(file_id("video"), None).finish();
+        assert!(mixed.is_ok());
 
-                self.media.push(MediaEntry::Video(edit_function(video)));
+        let documents = MediaGroup::build(1234)
+            .add_document(file_id("first"), None)
+            .add_document(file_id("second"), None)
+            .finish();
+        assert!(documents.is_ok());
+
+        match photos(2).add_audio(file_id("audio"), None).finish() {
+            Err(BotError::InvalidMediaGroup(err)) => {
+                assert_eq!(err, "Group media cannot mix photos and videos with audios")
             }
-            Uploader::Url(url) => {
-                let video = MediaVideo {
-                    r#type: "video".into(),
-                    media: url.0.clone(),
-                    ..Default::default()
-                };
+            other => panic!("Expected InvalidMediaGroup, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unique_attachments() {
+        let image = |name: &str| FileUploader::from_bytes(name, vec![1, 2, 3]);
 
-                self.media.push(MediaEntry::Video(edit_function(video)));
+        let group = MediaGroup::build(1234)
+            .add_photo(image("image.jpg"))
+            .add_photo(image("image.jpg"))
+            .add_video(
+                file_url("https://beep.bop/video.mp4"),
+                Some(image("image.jpg")),
+            )
+            .finish()
+            .expect("Invalid media group");
+
+        let names: Vec<&str> = group
+            .attachments
+            .iter()
+            .map(|attachment| attachment.name.as_str())
+            .collect();
+
+        assert_eq!(names, vec!["media0", "media1", "thumb2"]);
+        assert!(group
+            .media_encoded
+            .contains("\"thumb\":\"attach://thumb2\""));
+        assert!(group
+            .media_encoded
+            .contains("\"media\":\"https://beep.bop/video.mp4\""));
+    }
+
+    #[test]
+    fn caption_limit() {
+        let group = photos(1)
+            .add_photo_with(file_id("photo"), |photo| {
+                photo.with_caption("a".repeat(1025))
+            })
+            .finish();
+
+        match group {
+            Err(BotError::CaptionTooLong(length)) => assert_eq!(length, 1025),
+            other => panic!("Expected a long caption error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_attachments() {
+        let runtime = tokio::runtime::Runtime::new().expect("Unable to create a runtime");
+        let client = Client::new();
+
+        let large_photo = FileUploader::from_bytes("large.jpg", vec![0; 11 * 1024 * 1024]);
+        let png_thumbnail = FileUploader::new("res/brownpuppy.png").unwrap();
+
+        let group = MediaGroup::build(1234)
+            .add_photo(large_photo)
+            .add_video(file_id("video"), Some(png_thumbnail))
+            .finish()
+            .expect("Invalid media group");
+
+        let errors: Vec<UploaderError> = group
+            .attachments
+            .iter()
+            .filter_map(|attachment| runtime.block_on(attachment.validate(&client)).err())
+            .collect();
+
+        assert!(matches!(errors[0], UploaderError::FileTooLarge(..)));
+        assert!(matches!(errors[1], UploaderError::InvalidThumbnail(_)));
+    }
+
+    #[test]
+    fn split_albums() {
+        let album = photos(21)
+            .add_document(file_id("first"), None)
+            .add_document(file_id("second"), None)
+            .with_reply_to_message_id(42);
+
+        let parts = album.split_album().expect("Cannot split the album");
+
+        let sizes: Vec<usize> = parts
+            .iter()
+            .map(|part| match part {
+                AlbumPart::Group(group) => group.media_encoded.matches("\"media\"").count(),
+                AlbumPart::Single(_) => 1,
+            })
+            .collect();
+
+        assert_eq!(sizes, vec![10, 10, 1, 2]);
+
+        match (&parts[0], &parts[1]) {
+            (AlbumPart::Group(first), AlbumPart::Group(second)) => {
+                assert_eq!(first.query.reply_to_message_id, Some(42));
+                assert_eq!(second.query.reply_to_message_id, None);
             }
-            Uploader::Empty => (),
+            other => panic!("Expected two groups, found {:?}", other),
         }
 
-        self
+        match &parts[2] {
+            AlbumPart::Single(single) => {
+                assert_eq!(single.kind, MediaKind::Photo);
+                assert!(single.query["reply_to_message_id"].is_null());
+                assert!(!single.query.contains_key("media"));
+            }
+            other => panic!("Expected a single photo, found {:?}", other),
+        }
     }
 }
@@ -1,11 +1,21 @@
 use crate::telegram_request::TelegramRequest;
+use crate::upload_cache::MediaKind;
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::Client;
 use std::error::Error;
+use std::time::Duration;
+
+/// How long the size of an url is looked up before sending it anyway.
+const URL_SIZE_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug)]
 pub enum UploaderError {
     WrongMime(reqwest::Error),
     NoFileName,
     InvalidUTF,
+    /// A file over the telegram limit for the field, with its size and the limit.
+    FileTooLarge(String, u64, u64),
+    InvalidThumbnail(String),
 }
 
 impl std::fmt::Display for UploaderError {
@@ -14,6 +24,12 @@ impl std::fmt::Display for UploaderError {
             UploaderError::WrongMime(err) => err.fmt(f),
             UploaderError::NoFileName => "No file name.".fmt(f),
             UploaderError::InvalidUTF => "Invalid UTF-8 on path.".fmt(f),
+            UploaderError::FileTooLarge(field, size, limit) => write!(
+                f,
+                "The {} has {} bytes, over the limit of {} bytes.",
+                field, size, limit
+            ),
+            UploaderError::InvalidThumbnail(err) => err.fmt(f),
         }
     }
 }
@@ -24,6 +40,8 @@ impl Error for UploaderError {
             UploaderError::WrongMime(err) => err.description(),
             UploaderError::NoFileName => "No file name.",
             UploaderError::InvalidUTF => "Invalid UTF-8 on path",
+            UploaderError::FileTooLarge(..) => "File over the telegram size limit.",
+            UploaderError::InvalidThumbnail(err) => err,
         }
     }
 }
@@ -63,18 +81,67 @@ impl Uploader {
                 let mut request = builder.with_form_part(tag, file_uploader.part);
 
                 if let Some(thumbnail) = file_uploader.thumbnail {
-                    request = request.with_form_part("thumb", thumbnail);
+                    request = request.with_form_part("thumb", thumbnail.part);
                 }
 
                 request
             }
-            Uploader::Url(url) => builder.with_query(&[(tag, url.0)]),
-            Uploader::Id(id) => builder.with_query(&[(tag, id.0)]),
+            Uploader::Url(url) => builder.with_field(tag, url.0),
+            Uploader::Id(id) => builder.with_field(tag, id.0),
             Uploader::Empty => (builder),
         }
     }
 }
 
+impl Uploader {
+    /// Check the uploader is within the telegram limits for a kind
+    /// of media.
+    ///
+    /// Urls are checked on a best effort basis, by the size of a
+    /// HEAD request: an url is accepted when the request fails,
+    /// times out or the server does not answer with a size.
+    pub(crate) async fn validate(
+        &self,
+        kind: MediaKind,
+        client: &Client,
+    ) -> Result<(), UploaderError> {
+        match self {
+            Uploader::File(file_uploader) => file_uploader.validate(kind).await,
+            Uploader::Url(url) => {
+                let limit = kind.url_limit();
+
+                match url_size(client, &url.0).await {
+                    Some(size) if size > limit => Err(UploaderError::FileTooLarge(
+                        kind.field().to_owned(),
+                        size,
+                        limit,
+                    )),
+                    _ => Ok(()),
+                }
+            }
+            Uploader::Id(_) | Uploader::Empty => Ok(()),
+        }
+    }
+}
+
+/// The Content-Length of a url, when the server answers with one in time.
+async fn url_size(client: &Client, url: &str) -> Option<u64> {
+    let response = client
+        .head(url)
+        .timeout(URL_SIZE_TIMEOUT)
+        .send()
+        .await
+        .ok()?;
+
+    response
+        .headers()
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
 impl Default for Uploader {
     fn default() -> Self {
         Uploader::Empty
@@ -141,8 +208,12 @@ pub fn file_url<S: ToString>(url: S) -> UrlUploader {
 }
 
 pub mod action;
+pub mod answer_callback_query;
+pub mod answer_precheckout_query;
+pub mod answer_shipping_query;
 pub mod chat_id;
 pub mod edit_live_location;
+pub mod edit_message_reply_markup;
 pub mod export_chat_invite_link;
 pub mod file_uploader;
 pub mod force_reply;
@@ -152,15 +223,18 @@ pub mod get_chat_member;
 pub mod get_file;
 pub mod get_updates;
 pub mod get_user_profile_photos;
+pub mod inline_keyboard;
 pub mod inline_keyboard_button;
 pub mod inline_keyboard_button_message;
 pub mod inline_keyboard_markup;
 pub mod keyboard_button;
 pub mod kick_chat_member;
 pub mod media_group;
+pub mod parse_mode;
 pub mod pin_message;
 pub mod promote_chat_member;
 pub mod reply_board_markup;
+pub mod reply_keyboard;
 pub mod reply_keyboard_remove;
 pub mod reply_markup;
 pub mod restrict_chat_member;
@@ -169,6 +243,7 @@ pub mod send_audio;
 pub mod send_chat_action;
 pub mod send_contact;
 pub mod send_document;
+pub mod send_invoice;
 pub mod send_location;
 pub mod send_message;
 pub mod send_photo;
@@ -179,12 +254,18 @@ pub mod send_voice;
 pub mod set_chat_description;
 pub mod set_chat_sticker_set;
 pub mod set_chat_title;
+pub mod shipping_option;
 pub mod stop_live_location;
+pub mod transfer_target;
 pub mod unban_chat_member;
 
 pub use action::*;
+pub use answer_callback_query::*;
+pub use answer_precheckout_query::*;
+pub use answer_shipping_query::*;
 pub use chat_id::*;
 pub use edit_live_location::*;
+pub use edit_message_reply_markup::*;
 pub use export_chat_invite_link::*;
 pub use file_uploader::*;
 pub use force_reply::*;
@@ -194,15 +275,18 @@ pub use get_chat_member::*;
 pub use get_file::*;
 pub use get_updates::*;
 pub use get_user_profile_photos::*;
+pub use inline_keyboard::*;
 pub use inline_keyboard_button::*;
 pub use inline_keyboard_button_message::*;
 pub use inline_keyboard_markup::*;
 pub use keyboard_button::*;
 pub use kick_chat_member::*;
 pub use media_group::*;
+pub use parse_mode::*;
 pub use pin_message::*;
 pub use promote_chat_member::*;
 pub use reply_board_markup::*;
+pub use reply_keyboard::*;
 pub use reply_keyboard_remove::*;
 pub use reply_markup::*;
 pub use restrict_chat_member::*;
@@ -211,6 +295,7 @@ pub use send_audio::*;
 pub use send_chat_action::*;
 pub use send_contact::*;
 pub use send_document::*;
+pub use send_invoice::*;
 pub use send_location::*;
 pub use send_message::*;
 pub use send_photo::*;
@@ -221,5 +306,7 @@ pub use send_voice::*;
 pub use set_chat_description::*;
 pub use set_chat_sticker_set::*;
 pub use set_chat_title::*;
+pub use shipping_option::*;
 pub use stop_live_location::*;
+pub use transfer_target::*;
 pub use unban_chat_member::*;
@@ -0,0 +1,15 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// The markup used to format the text or caption of a message.
+///
+/// Markdown is kept for backwards compatibility,
+/// MarkdownV2 should be preferred.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ParseMode {
+    #[serde(rename = "HTML")]
+    Html,
+    #[serde(rename = "Markdown")]
+    Markdown,
+    #[serde(rename = "MarkdownV2")]
+    MarkdownV2,
+}
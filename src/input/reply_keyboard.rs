@@ -0,0 +1,188 @@
+use crate::error::BotError;
+use crate::input::{KeyboardButton, ReplyKeboardMarkup};
+
+/// The maximum number of buttons on a row of a reply keyboard.
+pub const REPLY_KEYBOARD_ROW_LIMIT: usize = 12;
+/// The maximum number of buttons on a reply keyboard.
+pub const REPLY_KEYBOARD_LIMIT: usize = 300;
+
+/// A row of buttons being built by a ReplyKeyboard.
+#[derive(Clone, Debug, Default)]
+pub struct ReplyRow {
+    buttons: Vec<KeyboardButton>,
+}
+
+impl ReplyRow {
+    /// Add a button sending its text as a message.
+    pub fn text<S: Into<String>>(mut self, text: S) -> Self {
+        self.buttons.push(KeyboardButton {
+            text: text.into(),
+            ..Default::default()
+        });
+
+        self
+    }
+
+    /// Add a button sending the user phone number.
+    pub fn contact<S: Into<String>>(mut self, text: S) -> Self {
+        self.buttons.push(KeyboardButton {
+            text: text.into(),
+            request_contact: true,
+            ..Default::default()
+        });
+
+        self
+    }
+
+    /// Add a button sending the user location.
+    pub fn location<S: Into<String>>(mut self, text: S) -> Self {
+        self.buttons.push(KeyboardButton {
+            text: text.into(),
+            request_location: true,
+            ..Default::default()
+        });
+
+        self
+    }
+}
+
+/// The ReplyKeyboard is a builder for ReplyKeboardMarkup.
+///
+/// ```
+/// use beepbop::input::{ReplyKeyboard, ReplyMarkup};
+///
+/// let markup: ReplyMarkup = ReplyKeyboard::new()
+///     .row(|r| r.text("Hello").location("Where am I?"))
+///     .resize()
+///     .one_time()
+///     .finish()
+///     .unwrap()
+///     .into();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReplyKeyboard {
+    markup: ReplyKeboardMarkup,
+    wrap: Option<usize>,
+}
+
+impl ReplyKeyboard {
+    pub fn new() -> Self {
+        ReplyKeyboard::default()
+    }
+
+    /// Wrap the rows added after this call so
+    /// they have at most `buttons` buttons each.
+    pub fn wrap(mut self, buttons: usize) -> Self {
+        self.wrap = Some(buttons.max(1));
+
+        self
+    }
+
+    /// Add a row of buttons, which can span
+    /// many rows if the keyboard is wrapped.
+    pub fn row<F>(mut self, build: F) -> Self
+    where
+        F: FnOnce(ReplyRow) -> ReplyRow,
+    {
+        let buttons = build(ReplyRow::default()).buttons;
+
+        match self.wrap {
+            Some(wrap) => self
+                .markup
+                .keyboard
+                .extend(buttons.chunks(wrap).map(|chunk| chunk.to_vec())),
+            None if !buttons.is_empty() => self.markup.keyboard.push(buttons),
+            None => (),
+        }
+
+        self
+    }
+
+    /// Ask the clients to fit the keyboard to its buttons.
+    pub fn resize(mut self) -> Self {
+        self.markup.resize_keyboard = true;
+
+        self
+    }
+
+    /// Ask the clients to hide the keyboard once it is used.
+    pub fn one_time(mut self) -> Self {
+        self.markup.one_time_keyboard = true;
+
+        self
+    }
+
+    /// Show the keyboard only to mentioned users
+    /// and to the sender of the replied message.
+    pub fn selective(mut self) -> Self {
+        self.markup.selective = true;
+
+        self
+    }
+
+    /// Finish the building of the ReplyKeboardMarkup,
+    /// checking it against the Telegram limits.
+    pub fn finish(self) -> Result<ReplyKeboardMarkup, BotError> {
+        let rows = &self.markup.keyboard;
+        let number_of_buttons: usize = rows.iter().map(Vec::len).sum();
+
+        if number_of_buttons > REPLY_KEYBOARD_LIMIT {
+            return Err(BotError::InvalidKeyboard(format!(
+                "Reply keyboards can have at most {} buttons, found {}",
+                REPLY_KEYBOARD_LIMIT, number_of_buttons
+            )));
+        }
+
+        for (index, row) in rows.iter().enumerate() {
+            if row.len() > REPLY_KEYBOARD_ROW_LIMIT {
+                return Err(BotError::InvalidKeyboard(format!(
+                    "Reply keyboard rows can have at most {} buttons, row {} has {}",
+                    REPLY_KEYBOARD_ROW_LIMIT,
+                    index,
+                    row.len()
+                )));
+            }
+
+            if row.iter().any(|button| button.text.is_empty()) {
+                return Err(BotError::InvalidKeyboard(
+                    "Reply keyboard buttons must have a text".to_string(),
+                ));
+            }
+        }
+
+        Ok(self.markup)
+    }
+}
+
+#[cfg(test)]
+mod reply_keyboard_tests {
+    use super::*;
+
+    #[test]
+    fn build_keyboard() {
+        let markup = ReplyKeyboard::new()
+            .wrap(3)
+            .row(|r| r.text("1").text("2").text("3").contact("Phone"))
+            .resize()
+            .one_time()
+            .finish()
+            .expect("Invalid keyboard");
+
+        assert_eq!(markup.keyboard.len(), 2);
+        assert!(markup.keyboard[1][0].request_contact);
+        assert!(markup.resize_keyboard);
+        assert!(markup.one_time_keyboard);
+        assert!(!markup.selective);
+    }
+
+    #[test]
+    fn telegram_limits() {
+        let long_row = ReplyKeyboard::new()
+            .row(|r| (0..13).fold(r, |r, n| r.text(n.to_string())))
+            .finish();
+        assert!(long_row.is_err());
+
+        let empty_text = ReplyKeyboard::new().row(|r| r.text("")).finish();
+        assert!(empty_text.is_err());
+    }
+}
@@ -2,6 +2,7 @@ use optbuilder::OptionalBuilder;
 use serde_derive::Serialize;
 
 use crate::input::ChatID;
+use crate::input::ParseMode;
 use crate::input::ReplyMarkup;
 use crate::input::Uploader;
 
@@ -10,7 +11,7 @@ pub struct SendAudio {
     pub chat_id: ChatID,
     pub voice: Uploader,
     pub caption: Option<String>,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     // Telegram Docs specifies this as integer(?).
     pub duration: Option<u32>,
     pub performer: Option<String>,
@@ -24,7 +25,7 @@ pub struct SendAudio {
 pub struct SendAudioQuery {
     pub chat_id: ChatID,
     pub caption: Option<String>,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     // Telegram Docs specifies this as integer(?).
     pub duration: Option<u32>,
     pub performer: Option<String>,
@@ -2,6 +2,7 @@ use optbuilder::OptionalBuilder;
 use serde_derive::Serialize;
 
 use crate::input::ChatID;
+use crate::input::ParseMode;
 use crate::input::ReplyMarkup;
 use crate::input::Uploader;
 
@@ -9,7 +10,7 @@ use crate::input::Uploader;
 pub struct SendDocumentQuery {
     pub chat_id: ChatID,
     pub caption: Option<String>,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     pub disable_notification: Option<bool>,
     pub reply_to_message_id: Option<i64>,
     pub reply_markup: Option<ReplyMarkup>,
@@ -20,7 +21,7 @@ pub struct SendDocument {
     pub document: Uploader,
     pub chat_id: ChatID,
     pub caption: Option<String>,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     pub disable_notification: Option<bool>,
     pub reply_to_message_id: Option<i64>,
     pub reply_markup: Option<ReplyMarkup>,
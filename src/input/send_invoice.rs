@@ -0,0 +1,63 @@
+use optbuilder::OptionalBuilder;
+use serde_derive::Serialize;
+
+use crate::input::InlineKeyboardMarkup;
+use crate::object::LabeledPrice;
+
+/// The SendInvoice struct holds all data required
+/// to send an invoice. The prices are nested objects,
+/// so this struct must be sent as a json body.
+#[derive(OptionalBuilder, Default, Debug, Serialize)]
+pub struct SendInvoice {
+    pub chat_id: i64,
+    pub title: String,
+    pub description: String,
+    pub payload: String,
+    pub provider_token: String,
+    pub start_parameter: String,
+    pub currency: String,
+    pub prices: Vec<LabeledPrice>,
+    pub provider_data: Option<String>,
+    pub photo_url: Option<String>,
+    pub photo_size: Option<u64>,
+    pub photo_width: Option<u32>,
+    pub photo_height: Option<u32>,
+    pub need_name: Option<bool>,
+    pub need_phone_number: Option<bool>,
+    pub need_email: Option<bool>,
+    pub need_shipping_address: Option<bool>,
+    pub send_phone_number_to_provider: Option<bool>,
+    pub send_email_to_provider: Option<bool>,
+    pub is_flexible: Option<bool>,
+    pub disable_notification: Option<bool>,
+    pub reply_to_message_id: Option<i64>,
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+impl SendInvoice {
+    /// Create a new invoice, the payload is not shown to
+    /// the user and can be used to identify the order later.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<S: Into<String>>(
+        chat_id: i64,
+        title: S,
+        description: S,
+        payload: S,
+        provider_token: S,
+        start_parameter: S,
+        currency: S,
+        prices: Vec<LabeledPrice>,
+    ) -> Self {
+        SendInvoice {
+            chat_id,
+            title: title.into(),
+            description: description.into(),
+            payload: payload.into(),
+            provider_token: provider_token.into(),
+            start_parameter: start_parameter.into(),
+            currency: currency.into(),
+            prices,
+            ..Default::default()
+        }
+    }
+}
@@ -14,7 +14,6 @@ pub struct SendLocation {
     pub live_period: Option<u32>,
     pub disable_notification: Option<bool>,
     pub reply_to_message_id: Option<i64>,
-    #[serde(flatten)]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
@@ -2,17 +2,17 @@ use optbuilder::OptionalBuilder;
 use serde_derive::Serialize;
 
 use crate::input::ChatID;
+use crate::input::ParseMode;
 use crate::input::ReplyMarkup;
 
 #[derive(OptionalBuilder, Debug, Default, Serialize)]
 pub struct SendMessage {
     pub chat_id: ChatID,
     pub text: String,
-    pub parse_mode: Option<String>,
+    pub parse_mode: Option<ParseMode>,
     pub disable_web_page_preview: Option<bool>,
     pub disable_notification: Option<bool>,
     pub reply_to_message_id: Option<i64>,
-    #[serde(flatten)]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
@@ -0,0 +1,20 @@
+use serde_derive::Serialize;
+
+use crate::object::LabeledPrice;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ShippingOption {
+    pub id: String,
+    pub title: String,
+    pub prices: Vec<LabeledPrice>,
+}
+
+impl ShippingOption {
+    pub fn new<S: Into<String>>(id: S, title: S, prices: Vec<LabeledPrice>) -> Self {
+        ShippingOption {
+            id: id.into(),
+            title: title.into(),
+            prices,
+        }
+    }
+}
@@ -10,7 +10,6 @@ pub struct StopLiveLocation {
     pub chat_id: Option<ChatID>,
     pub message_id: Option<i64>,
     pub inline_message_id: Option<String>,
-    #[serde(flatten)]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
@@ -0,0 +1,52 @@
+use optbuilder::OptionalBuilder;
+use serde_derive::Serialize;
+
+use crate::input::ChatID;
+use crate::input::ParseMode;
+use crate::upload_cache::MediaKind;
+
+/// The TransferTarget is the chat and kind of
+/// media a file is transferred as by Bot::transfer_file.
+#[derive(OptionalBuilder, Debug)]
+pub struct TransferTarget {
+    pub chat_id: ChatID,
+    pub kind: MediaKind,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub disable_notification: Option<bool>,
+    pub reply_to_message_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TransferTargetQuery {
+    pub chat_id: ChatID,
+    pub caption: Option<String>,
+    pub parse_mode: Option<ParseMode>,
+    pub disable_notification: Option<bool>,
+    pub reply_to_message_id: Option<i64>,
+}
+
+impl TransferTarget {
+    pub fn new<ID: Into<ChatID>>(chat_id: ID, kind: MediaKind) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            kind,
+            caption: None,
+            parse_mode: None,
+            disable_notification: None,
+            reply_to_message_id: None,
+        }
+    }
+
+    pub fn split(self) -> (TransferTargetQuery, MediaKind) {
+        let query = TransferTargetQuery {
+            chat_id: self.chat_id,
+            caption: self.caption,
+            parse_mode: self.parse_mode,
+            disable_notification: self.disable_notification,
+            reply_to_message_id: self.reply_to_message_id,
+        };
+
+        (query, self.kind)
+    }
+}
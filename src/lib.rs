@@ -8,62 +8,62 @@
 //!
 //! ## Actions
 //!
-//! Every action is supposed to be a function performed by the bot,
-//! returning a future with a reference to the bot and the data
-//! of the last action.
+//! Every action is an async function performed by the bot,
+//! returning the data of the action once awaited.
 //!
 //! ```rust
 //! use std::env::var;
 //! use beepbop::bot::Bot;
-//! use beepbop::object::User;
-//! use beepbop::tokio;
-//! use beepbop::futures::Future;
+//! use beepbop::tokio::runtime::Runtime;
 //!
 //! let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
 //!
 //! let bot = Bot::new(&api_key);
+//! let runtime = Runtime::new().expect("Unable to create a runtime");
 //!
-//! tokio::run(
-//!     bot
-//!         .get_me()
-//!         .map(|(_bot, me): (Bot, User)| println!("{:?}", me))
-//!         .map_err(|err| println!("{:?}", err))
-//! );
+//! match runtime.block_on(bot.get_me()) {
+//!     Ok(me) => println!("{:?}", me),
+//!     Err(err) => println!("{:?}", err),
+//! }
 //! ```
 //!
 //! ## Multiple Actions
 //!
-//! Actions can be chained with the use of the and_then combinator.
+//! Actions can be awaited one after the other, since they
+//! only borrow the bot.
 //!
 //!
 //! ```rust
 //! use std::env::var;
 //! use beepbop::bot::Bot;
-//! use beepbop::tokio;
-//! use beepbop::futures::Future;
+//! use beepbop::tokio::runtime::Runtime;
 //!
 //! let api_key = var("API_KEY").expect("Cannot find API_KEY in ENV");
 //! let chat_id = var("CHAT_ID").expect("Cannot find CHAT_ID in ENV");
 //!
 //! let bot = Bot::new(&api_key);
+//! let runtime = Runtime::new().expect("Unable to create a runtime");
 //!
-//! tokio::run(
-//!     bot
-//!         .get_chat(chat_id)
-//!         .and_then(|(bot, chat)| {
-//!             let file_id = chat.photo.unwrap().big_file_id;
+//! let save_photo = async {
+//!     let chat = bot.get_chat(chat_id).await?;
+//!     let file_id = chat.photo.unwrap().big_file_id;
 //!
-//!             bot.download_file(file_id)
-//!         })
-//!         .and_then(|(_, file_buffer)| {
-//!             let save_name = file_buffer.name.replace("/", "_");
+//!     let file_buffer = bot.download_file(file_id).await?;
+//!     let save_name = file_buffer.name.replace("/", "_");
 //!
-//!             file_buffer.save_as(format!("res/{}", save_name))
-//!         })
-//!         .map_err(|err| println!("{:?}", err))
-//! );
+//!     file_buffer.save_as(format!("res/{}", save_name)).await
+//! };
 //!
+//! if let Err(err) = runtime.block_on(save_photo) {
+//!     println!("{:?}", err);
+//! }
 //! ```
+//!
+//! ## Compatibility
+//!
+//! The combinator API of futures 0.1, where every action
+//! returns the bot with the data, is kept on the compat
+//! module, behind the `compat` feature.
 
 /// The prelude module contains a few commonly used modules and objects.
 pub mod prelude {
@@ -77,12 +77,21 @@ pub mod prelude {
     pub use crate::tokio;
 }
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod bot;
+pub mod callback;
+#[cfg(feature = "compat")]
+pub mod compat;
 pub mod error;
+pub mod formatting;
 pub mod input;
+pub mod menu;
 pub mod object;
+pub mod payments;
 pub mod stream;
 pub mod telegram_request;
+pub mod upload_cache;
 
 mod macros;
 
@@ -90,5 +99,7 @@ mod macros;
 pub mod tests;
 
 pub use futures;
+#[cfg(feature = "compat")]
+pub use futures01;
 pub use reqwest;
 pub use tokio;
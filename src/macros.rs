@@ -3,17 +3,12 @@
 /// and error of a future.
 macro_rules! log_all {
     ($future_obj:expr) => {
-        $future_obj
-            .map(|data| {
-                println!("{:#?}", data);
-
-                ()
-            })
-            .map_err(|err| {
-                println!("{:#?}", err);
-
-                ()
-            })
+        async move {
+            match $future_obj.await {
+                Ok(data) => println!("{:#?}", data),
+                Err(err) => println!("{:#?}", err),
+            }
+        }
     };
 }
 
@@ -22,11 +17,13 @@ macro_rules! log_all {
 /// and ignoring the result.
 macro_rules! log_error {
     ($future_obj:expr) => {
-        $future_obj.map_err(|err| {
-            println!("{:#?}", err);
+        async move {
+            $future_obj.await.map_err(|err| {
+                println!("{:#?}", err);
 
-            ()
-        })
+                ()
+            })
+        }
     };
 }
 
@@ -35,10 +32,12 @@ macro_rules! log_error {
 /// and ignoring the error.
 macro_rules! log_data {
     ($future_obj:expr) => {
-        $future_obj.map(|data| {
-            println!("{:#?}", data);
+        async move {
+            $future_obj.await.map(|data| {
+                println!("{:#?}", data);
 
-            ()
-        })
+                ()
+            })
+        }
     };
 }
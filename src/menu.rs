@@ -0,0 +1,413 @@
+//! The Menu module provides a paginated inline menu,
+//! listing items as buttons with navigation buttons
+//! to move between pages of the same message.
+//!
+//! Navigation is handled by the menu, editing the keyboard
+//! of the original message, while selections are given
+//! back to be handled by the bot.
+use std::sync::Arc;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::bot::{Bot, BotResult};
+use crate::callback::CallbackCodec;
+use crate::error::BotError;
+use crate::input::{
+    AnswerCallbackQuery, ChatID, EditMessageReplyMarkup, InlineKeyboard, InlineKeyboardMarkup,
+    SendMessage, INLINE_KEYBOARD_ROW_LIMIT,
+};
+use crate::object::{CallbackQuery, Message};
+
+/// An item of a menu, shown as a button with the label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuItem {
+    pub id: String,
+    pub label: String,
+}
+
+impl MenuItem {
+    pub fn new<S: Into<String>>(id: S, label: S) -> Self {
+        MenuItem {
+            id: id.into(),
+            label: label.into(),
+        }
+    }
+}
+
+/// A MenuSource gives the items of a menu, page by page,
+/// so large lists do not have to be kept in memory.
+pub trait MenuSource: Send + Sync {
+    /// The number of items on the source.
+    fn count(&self) -> usize;
+    /// At most limit items, starting at offset.
+    fn items(&self, offset: usize, limit: usize) -> Vec<MenuItem>;
+}
+
+impl MenuSource for Vec<MenuItem> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn items(&self, offset: usize, limit: usize) -> Vec<MenuItem> {
+        self.iter().skip(offset).take(limit).cloned().collect()
+    }
+}
+
+/// The callback data of the menu buttons, short
+/// names keep it within the callback data limit.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum MenuAction {
+    #[serde(rename = "p")]
+    Page(String, usize),
+    #[serde(rename = "s")]
+    Select(String, String),
+    #[serde(rename = "i")]
+    Ignore(String),
+}
+
+/// An event of a callback query for a menu.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuEvent {
+    /// Move to another page.
+    Navigate(usize),
+    /// An item was selected, by id.
+    Select(String),
+    /// The page indicator was pressed.
+    Ignore,
+}
+
+/// An item selected on a menu, the query
+/// must still be answered by the handler.
+#[derive(Clone, Debug)]
+pub struct MenuSelection {
+    pub item_id: String,
+    pub query: CallbackQuery,
+}
+
+/// The PaginatedMenu renders the pages of a MenuSource
+/// and handles the callback queries of its buttons.
+///
+/// The name identifies the menu on the callback data,
+/// so many menus can be used by the same bot.
+#[derive(Clone)]
+pub struct PaginatedMenu {
+    name: String,
+    source: Arc<dyn MenuSource>,
+    page_size: usize,
+    columns: usize,
+    previous_label: String,
+    next_label: String,
+    codec: CallbackCodec,
+}
+
+impl std::fmt::Debug for PaginatedMenu {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "PaginatedMenu:{}", self.name)
+    }
+}
+
+impl PaginatedMenu {
+    pub fn new<N, S>(name: N, source: S, page_size: usize) -> Self
+    where
+        N: Into<String>,
+        S: MenuSource + 'static,
+    {
+        PaginatedMenu {
+            name: name.into(),
+            source: Arc::new(source),
+            page_size: page_size.max(1),
+            columns: 1,
+            previous_label: "◀ Prev".to_string(),
+            next_label: "Next ▶".to_string(),
+            codec: CallbackCodec::new(),
+        }
+    }
+
+    /// Show the items in rows of many buttons.
+    pub fn with_columns(mut self, columns: usize) -> Self {
+        self.columns = columns.max(1);
+
+        self
+    }
+
+    /// Replace the labels of the navigation buttons.
+    pub fn with_labels<S: Into<String>>(mut self, previous: S, next: S) -> Self {
+        self.previous_label = previous.into();
+        self.next_label = next.into();
+
+        self
+    }
+
+    /// Encode the callback data with this codec,
+    /// to sign it or to allow long item ids.
+    pub fn with_codec(mut self, codec: CallbackCodec) -> Self {
+        self.codec = codec;
+
+        self
+    }
+
+    /// The number of pages, at least one.
+    pub fn pages(&self) -> usize {
+        let count = self.source.count();
+
+        count.div_ceil(self.page_size).max(1)
+    }
+
+    fn encode(&self, action: &MenuAction) -> Result<String, BotError> {
+        self.codec
+            .encode(action)
+            .map_err(|err| BotError::InvalidKeyboard(err.to_string()))
+    }
+
+    /// Render the keyboard of a page, pages past
+    /// the end are rendered as the last page.
+    pub fn markup(&self, page: usize) -> Result<InlineKeyboardMarkup, BotError> {
+        let pages = self.pages();
+        let page = page.min(pages - 1);
+
+        let items = self
+            .source
+            .items(page * self.page_size, self.page_size)
+            .into_iter()
+            .map(|item| {
+                let data = self.encode(&MenuAction::Select(self.name.clone(), item.id))?;
+
+                Ok((item.label, data))
+            })
+            .collect::<Result<Vec<(String, String)>, BotError>>()?;
+
+        let mut keyboard = InlineKeyboard::new()
+            .wrap(self.columns)
+            .row(|row| {
+                items
+                    .into_iter()
+                    .fold(row, |row, (label, data)| row.callback(label, data))
+            })
+            // The navigation row must stay on a single row.
+            .wrap(INLINE_KEYBOARD_ROW_LIMIT);
+
+        if pages > 1 {
+            let mut navigation = Vec::new();
+
+            if page > 0 {
+                let data = self.encode(&MenuAction::Page(self.name.clone(), page - 1))?;
+                navigation.push((self.previous_label.clone(), data));
+            }
+
+            let data = self.encode(&MenuAction::Ignore(self.name.clone()))?;
+            navigation.push((format!("{}/{}", page + 1, pages), data));
+
+            if page + 1 < pages {
+                let data = self.encode(&MenuAction::Page(self.name.clone(), page + 1))?;
+                navigation.push((self.next_label.clone(), data));
+            }
+
+            keyboard = keyboard.row(|row| {
+                navigation
+                    .into_iter()
+                    .fold(row, |row, (label, data)| row.callback(label, data))
+            });
+        }
+
+        keyboard.finish()
+    }
+
+    /// Send the first page of the menu with a text.
+    pub async fn send<ID: Into<ChatID>, S: Into<String>>(
+        &self,
+        bot: &Bot,
+        chat_id: ID,
+        text: S,
+    ) -> BotResult<Message> {
+        let markup = self.markup(0)?;
+
+        bot.send_message(SendMessage::new(chat_id.into(), text.into()).with_reply_markup(markup))
+            .await
+    }
+
+    /// The event of a callback query, None if
+    /// the query is not from a button of this menu.
+    pub fn event(&self, query: &CallbackQuery) -> Option<MenuEvent> {
+        match self.codec.decode_query(query).ok()? {
+            MenuAction::Page(name, page) if name == self.name => Some(MenuEvent::Navigate(page)),
+            MenuAction::Select(name, id) if name == self.name => Some(MenuEvent::Select(id)),
+            MenuAction::Ignore(name) if name == self.name => Some(MenuEvent::Ignore),
+            _ => None,
+        }
+    }
+
+    /// Edit the keyboard of the message of a query.
+    async fn edit_markup(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        markup: InlineKeyboardMarkup,
+    ) -> BotResult<()> {
+        match (query.message.as_ref(), query.inline_message_id.as_ref()) {
+            (Some(message), _) => {
+                let edit =
+                    EditMessageReplyMarkup::new(message.chat.id, message.message_id, Some(markup));
+
+                bot.edit_message_reply_markup(edit).await.map(|_| ())
+            }
+            (None, Some(inline_message_id)) => {
+                let edit = EditMessageReplyMarkup::inline(inline_message_id.as_str(), Some(markup));
+
+                bot.edit_inline_reply_markup(edit).await.map(|_| ())
+            }
+            (None, None) => Err(BotError::InvalidKeyboard(
+                "Callback query without a message to edit".to_string(),
+            )),
+        }
+    }
+
+    /// Handle a callback query, moving between pages by
+    /// editing the keyboard of the message of the query.
+    ///
+    /// Returns the selection when an item was selected,
+    /// queries from other menus are left unanswered.
+    pub async fn handle(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+    ) -> BotResult<Option<MenuSelection>> {
+        let answer = AnswerCallbackQuery::new(query.id.as_str());
+
+        match self.event(query) {
+            Some(MenuEvent::Navigate(page)) => {
+                let edited = match self.markup(page) {
+                    Ok(markup) => self.edit_markup(bot, query, markup).await,
+                    Err(err) => Err(err),
+                };
+
+                // The query is always answered, so the client stops loading.
+                let answered = bot.answer_callback_query(answer).await;
+
+                match edited {
+                    Err(ref err) if is_not_modified(err) => (),
+                    edited => edited?,
+                }
+
+                answered?;
+
+                Ok(None)
+            }
+            Some(MenuEvent::Select(item_id)) => Ok(Some(MenuSelection {
+                item_id,
+                query: query.clone(),
+            })),
+            Some(MenuEvent::Ignore) => {
+                bot.answer_callback_query(answer).await?;
+
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Whether telegram refused an edit leaving the message as it
+/// was, which happens when a button is tapped twice.
+fn is_not_modified(err: &BotError) -> bool {
+    match err {
+        BotError::TelegramError(description) => description
+            .to_lowercase()
+            .contains("message is not modified"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod menu_tests {
+    use super::*;
+    use crate::input::InlineKeyboardButtonMessage;
+
+    fn menu() -> PaginatedMenu {
+        let items: Vec<MenuItem> = (1..=7)
+            .map(|n| MenuItem::new(format!("order-{}", n), format!("Order {}", n)))
+            .collect();
+
+        PaginatedMenu::new("orders", items, 3)
+    }
+
+    fn labels(markup: &InlineKeyboardMarkup) -> Vec<Vec<&str>> {
+        markup
+            .inline_keyboard
+            .iter()
+            .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+            .collect()
+    }
+
+    fn query(data: &str) -> CallbackQuery {
+        let json = serde_json::json!({
+            "id": "query",
+            "from": {"id": 1, "is_bot": false, "first_name": "User"},
+            "chat_instance": "instance",
+            "data": data,
+        });
+
+        serde_json::from_value(json).expect("Invalid callback query")
+    }
+
+    fn data(markup: &InlineKeyboardMarkup, row: usize, column: usize) -> String {
+        match &markup.inline_keyboard[row][column].inline_message {
+            InlineKeyboardButtonMessage::CallbackData(data) => data.clone(),
+            _ => panic!("Button without callback data"),
+        }
+    }
+
+    #[test]
+    fn render_pages() {
+        let menu = menu();
+        assert_eq!(menu.pages(), 3);
+
+        let first = menu.markup(0).unwrap();
+        assert_eq!(
+            labels(&first),
+            vec![
+                vec!["Order 1"],
+                vec!["Order 2"],
+                vec!["Order 3"],
+                vec!["1/3", "Next ▶"]
+            ]
+        );
+
+        let last = menu.with_columns(2).markup(5).unwrap();
+        assert_eq!(labels(&last), vec![vec!["Order 7"], vec!["◀ Prev", "3/3"]]);
+    }
+
+    #[test]
+    fn decode_events() {
+        let menu = menu();
+        let markup = menu.markup(1).unwrap();
+
+        assert_eq!(
+            menu.event(&query(&data(&markup, 0, 0))),
+            Some(MenuEvent::Select("order-4".to_string()))
+        );
+        assert_eq!(
+            menu.event(&query(&data(&markup, 3, 0))),
+            Some(MenuEvent::Navigate(0))
+        );
+        assert_eq!(
+            menu.event(&query(&data(&markup, 3, 2))),
+            Some(MenuEvent::Navigate(2))
+        );
+
+        let other_menu = PaginatedMenu::new("files", Vec::new(), 3);
+        assert_eq!(other_menu.event(&query(&data(&markup, 0, 0))), None);
+    }
+
+    #[test]
+    fn ignore_unmodified_edits() {
+        assert!(is_not_modified(&BotError::TelegramError(
+            "Bad Request: message is not modified".to_string()
+        )));
+        assert!(!is_not_modified(&BotError::TelegramError(
+            "Bad Request: message to edit not found".to_string()
+        )));
+        assert!(!is_not_modified(&BotError::InvalidKeyboard(
+            "message is not modified".to_string()
+        )));
+    }
+}
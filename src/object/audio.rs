@@ -0,0 +1,15 @@
+use serde_derive::Deserialize;
+
+use crate::object::PhotoSize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Audio {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub duration: u32,
+    pub performer: Option<String>,
+    pub title: Option<String>,
+    pub mime_type: Option<String>,
+    pub file_size: Option<u64>,
+    pub thumb: Option<PhotoSize>,
+}
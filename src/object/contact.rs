@@ -0,0 +1,10 @@
+use serde_derive::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Contact {
+    pub phone_number: String,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub user_id: Option<i64>,
+    pub vcard: Option<String>,
+}
@@ -0,0 +1,8 @@
+use serde_derive::Deserialize;
+
+/// A Dice is an animated emoji showing a random value.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Dice {
+    pub emoji: String,
+    pub value: u32,
+}
@@ -0,0 +1,13 @@
+use serde_derive::Deserialize;
+
+use crate::object::PhotoSize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Document {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub thumb: Option<PhotoSize>,
+    pub file_name: Option<String>,
+    pub mime_type: Option<String>,
+    pub file_size: Option<u64>,
+}
@@ -0,0 +1,9 @@
+use serde_derive::Deserialize;
+
+/// The credentials to decrypt the elements of a PassportData.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EncryptedCredentials {
+    pub data: String,
+    pub hash: String,
+    pub secret: String,
+}
@@ -0,0 +1,19 @@
+use serde_derive::Deserialize;
+
+use crate::object::PassportFile;
+
+/// A document or personal detail shared with Telegram Passport,
+/// encrypted with the credentials of its PassportData.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EncryptedPassportElement {
+    pub r#type: String,
+    pub data: Option<String>,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub files: Option<Vec<PassportFile>>,
+    pub front_side: Option<PassportFile>,
+    pub reverse_side: Option<PassportFile>,
+    pub selfie: Option<PassportFile>,
+    pub translation: Option<Vec<PassportFile>>,
+    pub hash: String,
+}
@@ -1,11 +1,16 @@
-use std::error::Error;
-use std::path::Path;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use futures::Future;
-use tokio::io::AsyncWrite;
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
 
 use crate::error::BotError;
 
+/// Counter keeping temporary file names unique
+/// between concurrent saves on the same process.
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
 /// A FileBuffer is a struct
 /// representing a file returned
 /// from telegram. It has a filename
@@ -20,6 +25,37 @@ pub struct FileBuffer {
     pub name: String,
 }
 
+/// The SaveOptions define how a FileBuffer or
+/// FileStream is written to the file system.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SaveOptions {
+    /// Write to a temporary file renamed to the path once
+    /// complete, so the path never holds a partial file.
+    pub atomic: bool,
+    /// Create the missing parent directories of the path.
+    pub create_dirs: bool,
+}
+
+impl SaveOptions {
+    pub fn new() -> Self {
+        SaveOptions::default()
+    }
+
+    /// Write to a temporary file and rename it.
+    pub fn atomic(mut self) -> Self {
+        self.atomic = true;
+
+        self
+    }
+
+    /// Create the missing parent directories.
+    pub fn create_dirs(mut self) -> Self {
+        self.create_dirs = true;
+
+        self
+    }
+}
+
 impl FileBuffer {
     /// Create a new file buffer from a string and data.
     pub fn new(name: String, data: Vec<u8>) -> Self {
@@ -27,13 +63,133 @@ impl FileBuffer {
     }
 
     /// Save the file buffer to a file path.
-    pub fn save_as<P>(self, path: P) -> impl Future<Item = (), Error = BotError>
-    where
-        P: AsRef<Path> + Send + 'static,
-    {
-        tokio::fs::File::create(path)
-            .and_then(move |mut file| file.poll_write(&self.data))
-            .map(|_| ())
-            .map_err(|err| BotError::DownloadError(err.description().to_owned()))
+    pub async fn save_as<P: AsRef<Path>>(&self, path: P) -> Result<(), BotError> {
+        self.save_with(path, SaveOptions::new()).await
+    }
+
+    /// Save the file buffer to a file path, with options.
+    pub async fn save_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: SaveOptions,
+    ) -> Result<(), BotError> {
+        save_file(path.as_ref(), options, |path| async move {
+            Ok(self.write_file(&path).await?)
+        })
+        .await
+    }
+
+    /// Write all the data to a file and wait until it reaches the disk.
+    async fn write_file(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = File::create(path).await?;
+
+        file.write_all(&self.data).await?;
+        file.flush().await?;
+        file.sync_all().await
+    }
+}
+
+/// Save a file following the options, the write closure writes
+/// it either at path or at a temporary path renamed once written.
+pub(crate) async fn save_file<T, W, F>(
+    path: &Path,
+    options: SaveOptions,
+    write: W,
+) -> Result<T, BotError>
+where
+    W: FnOnce(PathBuf) -> F,
+    F: Future<Output = Result<T, BotError>>,
+{
+    if options.create_dirs {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).await?;
+        }
+    }
+
+    if !options.atomic {
+        return write(path.to_path_buf()).await;
+    }
+
+    let temporary_path = temporary_path(path);
+
+    let written = match write(temporary_path.clone()).await {
+        Ok(value) => fs::rename(&temporary_path, path)
+            .await
+            .map(|()| value)
+            .map_err(BotError::from),
+        Err(err) => Err(err),
+    };
+
+    if written.is_err() {
+        // The original error matters more than a failed cleanup.
+        let _ = fs::remove_file(&temporary_path).await;
+    }
+
+    written
+}
+
+/// A hidden path on the same directory, so
+/// the rename does not cross file systems.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        TEMPORARY_FILES.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+#[cfg(test)]
+mod file_buffer_tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("beepbop-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    #[test]
+    fn save_atomic_with_dirs() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let dir = test_dir("atomic");
+        let path = dir.join("photos").join("puppy.jpg");
+
+        let file_buffer = FileBuffer::new("photos/file_1.jpg".to_string(), vec![7; 1024]);
+        let options = SaveOptions::new().atomic().create_dirs();
+
+        runtime
+            .block_on(file_buffer.save_with(&path, options))
+            .expect("Cannot save the file");
+
+        assert_eq!(std::fs::read(&path).unwrap(), vec![7; 1024]);
+
+        let files = std::fs::read_dir(dir.join("photos")).unwrap().count();
+        assert_eq!(files, 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_directory() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let path = test_dir("missing").join("puppy.jpg");
+
+        let file_buffer = FileBuffer::new("file_1.jpg".to_string(), vec![7; 16]);
+
+        match runtime.block_on(file_buffer.save_with(&path, SaveOptions::new().atomic())) {
+            Err(BotError::IoError(err)) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("Expected an IoError, found {:?}", other),
+        }
     }
 }
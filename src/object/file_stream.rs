@@ -0,0 +1,201 @@
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use tokio::fs::File;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::error::BotError;
+use crate::object::file_buffer::{save_file, SaveOptions};
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<Bytes, BotError>> + Send>>;
+type ProgressCallback = Box<dyn FnMut(u64, Option<u64>) + Send>;
+
+/// A FileStream is a file being downloaded
+/// from telegram, chunk by chunk.
+///
+/// Unlike the FileBuffer, the file is never
+/// kept entirely in memory.
+pub struct FileStream {
+    pub name: String,
+    /// The size of the file, when telegram knows it.
+    pub size: Option<u64>,
+    received: u64,
+    chunks: ChunkStream,
+    progress: Option<ProgressCallback>,
+}
+
+impl std::fmt::Debug for FileStream {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "FileStream:{} ({}/{:?} bytes)",
+            self.name, self.received, self.size
+        )
+    }
+}
+
+impl FileStream {
+    /// Create a new file stream from a stream of chunks.
+    pub fn new<S>(name: String, size: Option<u64>, chunks: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, BotError>> + Send + 'static,
+    {
+        FileStream {
+            name,
+            size,
+            received: 0,
+            chunks: Box::pin(chunks),
+            progress: None,
+        }
+    }
+
+    /// Call the closure on every chunk with the bytes
+    /// received so far and the size of the file.
+    pub fn with_progress<F>(mut self, progress: F) -> Self
+    where
+        F: FnMut(u64, Option<u64>) + Send + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+
+        self
+    }
+
+    /// The bytes received so far.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// Write every chunk to a writer as it arrives,
+    /// returning the number of bytes written.
+    pub async fn write_to<W>(mut self, writer: &mut W) -> Result<u64, BotError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        while let Some(chunk) = self.next().await {
+            writer.write_all(&chunk?).await?;
+        }
+
+        writer.flush().await?;
+
+        Ok(self.received)
+    }
+
+    /// Save the file to a path with options, returning the number
+    /// of bytes written.
+    ///
+    /// With atomic options a failed download never
+    /// replaces the file at path with a partial one.
+    pub async fn save_with<P: AsRef<Path>>(
+        self,
+        path: P,
+        options: SaveOptions,
+    ) -> Result<u64, BotError> {
+        save_file(path.as_ref(), options, |path| async move {
+            let mut file = File::create(path).await?;
+            let written = self.write_to(&mut file).await?;
+
+            file.sync_all().await?;
+
+            Ok(written)
+        })
+        .await
+    }
+}
+
+impl Stream for FileStream {
+    type Item = Result<Bytes, BotError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.chunks.as_mut().poll_next(cx);
+
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            self.received += chunk.len() as u64;
+
+            let (received, size) = (self.received, self.size);
+
+            if let Some(progress) = self.progress.as_mut() {
+                progress(received, size);
+            }
+        }
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod file_stream_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn write_chunks() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+
+        let chunks = vec![Ok(Bytes::from("beep ")), Ok(Bytes::from("bop"))];
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let progress_reports = reports.clone();
+
+        let stream = FileStream::new(
+            "beep.txt".to_string(),
+            Some(8),
+            futures::stream::iter(chunks),
+        )
+        .with_progress(move |received, size| {
+            progress_reports.lock().unwrap().push((received, size));
+        });
+
+        let mut written = Vec::new();
+        let count = runtime
+            .block_on(stream.write_to(&mut written))
+            .expect("Cannot write the stream");
+
+        assert_eq!(count, 8);
+        assert_eq!(written, b"beep bop");
+        assert_eq!(*reports.lock().unwrap(), vec![(5, Some(8)), (8, Some(8))]);
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+
+        let chunks = vec![
+            Ok(Bytes::from("beep")),
+            Err(BotError::DownloadError("Connection reset".to_string())),
+            Ok(Bytes::from("bop")),
+        ];
+
+        let stream = FileStream::new("beep.txt".to_string(), None, futures::stream::iter(chunks));
+
+        let mut written = Vec::new();
+        assert!(runtime.block_on(stream.write_to(&mut written)).is_err());
+        assert_eq!(written, b"beep");
+    }
+
+    #[test]
+    fn keep_file_on_failed_save() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let dir = std::env::temp_dir().join(format!("beepbop-stream-{}", std::process::id()));
+        let path = dir.join("beep.txt");
+
+        let chunks = vec![
+            Ok(Bytes::from("beep")),
+            Err(BotError::DownloadError("Connection reset".to_string())),
+        ];
+        let stream = FileStream::new("beep.txt".to_string(), None, futures::stream::iter(chunks));
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "old beep").unwrap();
+
+        let saved = runtime.block_on(stream.save_with(&path, SaveOptions::new().atomic()));
+
+        assert!(saved.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"old beep");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
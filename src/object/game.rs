@@ -0,0 +1,13 @@
+use serde_derive::Deserialize;
+
+use crate::object::{Animation, MessageEntity, PhotoSize};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Game {
+    pub title: String,
+    pub description: String,
+    pub photo: Vec<PhotoSize>,
+    pub text: Option<String>,
+    pub text_entities: Option<Vec<MessageEntity>>,
+    pub animation: Option<Animation>,
+}
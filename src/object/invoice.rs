@@ -0,0 +1,10 @@
+use serde_derive::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Invoice {
+    pub title: String,
+    pub description: String,
+    pub start_parameter: String,
+    pub currency: String,
+    pub total_amount: u64,
+}
@@ -1,7 +1,16 @@
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct LabeledPrice {
     pub label: String,
     pub amount: u64,
 }
+
+impl LabeledPrice {
+    pub fn new<S: Into<String>>(label: S, amount: u64) -> Self {
+        LabeledPrice {
+            label: label.into(),
+            amount,
+        }
+    }
+}
@@ -1,13 +1,149 @@
 use serde_derive::Deserialize;
 
-use crate::object::{Chat, User};
+use crate::input::InlineKeyboardMarkup;
+use crate::object::{
+    Animation, Audio, Chat, Contact, Dice, Document, Game, Invoice, Location, MessageEntity,
+    PassportData, PhotoSize, Poll, Sticker, SuccessfulPayment, User, Venue, Video, VideoNote,
+    Voice,
+};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Message {
     pub message_id: i64,
     pub date: i64,
-    pub from: User,
+    /// Empty for messages sent to channels.
+    pub from: Option<User>,
+    /// The chat that sent the message, for channel posts
+    /// and anonymous messages on behalf of a group.
+    pub sender_chat: Option<Box<Chat>>,
     pub chat: Box<Chat>,
+    pub forward_from: Option<User>,
+    pub forward_from_chat: Option<Box<Chat>>,
+    pub forward_from_message_id: Option<i64>,
+    pub forward_signature: Option<String>,
+    pub forward_sender_name: Option<String>,
+    pub forward_date: Option<i64>,
+    pub reply_to_message: Option<Box<Message>>,
+    pub edit_date: Option<i64>,
+    pub media_group_id: Option<String>,
+    pub author_signature: Option<String>,
     pub text: Option<String>,
+    pub entities: Option<Vec<MessageEntity>>,
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    pub audio: Option<Audio>,
+    pub document: Option<Document>,
+    pub animation: Option<Animation>,
+    pub photo: Option<Vec<PhotoSize>>,
+    pub sticker: Option<Sticker>,
+    pub video: Option<Video>,
+    pub voice: Option<Voice>,
+    pub video_note: Option<VideoNote>,
+    pub caption: Option<String>,
+    pub contact: Option<Contact>,
+    pub dice: Option<Dice>,
+    pub game: Option<Game>,
+    pub poll: Option<Poll>,
+    pub location: Option<Location>,
+    pub venue: Option<Venue>,
     pub new_chat_members: Option<Vec<User>>,
+    pub left_chat_member: Option<User>,
+    pub new_chat_title: Option<String>,
+    pub new_chat_photo: Option<Vec<PhotoSize>>,
+    pub delete_chat_photo: Option<bool>,
+    pub group_chat_created: Option<bool>,
+    pub supergroup_chat_created: Option<bool>,
+    pub channel_chat_created: Option<bool>,
+    pub migrate_to_chat_id: Option<i64>,
+    pub migrate_from_chat_id: Option<i64>,
+    pub pinned_message: Option<Box<Message>>,
+    pub invoice: Option<Invoice>,
+    pub successful_payment: Option<SuccessfulPayment>,
+    pub connected_website: Option<String>,
+    pub passport_data: Option<PassportData>,
+    /// The inline keyboard attached to the message,
+    /// login and game buttons are kept as sent.
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+#[cfg(test)]
+mod message_tests {
+    use crate::object::Message;
+
+    #[test]
+    fn document_reply() {
+        let json = r#"{
+            "message_id": 12,
+            "date": 1564000000,
+            "from": {"id": 1, "is_bot": false, "first_name": "Sender"},
+            "chat": {"id": 1, "type": "private", "first_name": "Sender"},
+            "caption": "Some text",
+            "document": {
+                "file_id": "BQADAQADtwADlLfNR6y",
+                "file_unique_id": "AgADtwADlLfNRw",
+                "file_name": "some_text",
+                "mime_type": "text/plain",
+                "file_size": 10,
+                "thumb": {"file_id": "AAQBAB", "file_unique_id": "AQADAB", "width": 90, "height": 90}
+            },
+            "reply_to_message": {
+                "message_id": 11,
+                "date": 1563999999,
+                "from": {"id": 2, "is_bot": true, "first_name": "Bot"},
+                "chat": {"id": 1, "type": "private", "first_name": "Sender"},
+                "text": "Send me a file",
+                "entities": [{"type": "bold", "offset": 8, "length": 2}]
+            }
+        }"#;
+
+        let message: Message = serde_json::from_str(json).expect("Invalid message");
+
+        let document = message.document.expect("Message without document");
+        assert_eq!(document.file_name, Some("some_text".to_string()));
+        assert_eq!(message.caption, Some("Some text".to_string()));
+
+        let reply = message.reply_to_message.expect("Message without reply");
+        assert_eq!(reply.message_id, 11);
+        assert_eq!(reply.entities.map(|entities| entities.len()), Some(1));
+    }
+
+    #[test]
+    fn poll_dice_and_keyboard() {
+        let json = serde_json::json!({
+            "message_id": 13,
+            "date": 1564000000,
+            "chat": {"id": 1, "type": "private", "first_name": "Sender"},
+            "poll": {
+                "id": "poll",
+                "question": "Beep or bop?",
+                "options": [{"text": "Beep", "voter_count": 2}, {"text": "Bop", "voter_count": 1}],
+                "total_voter_count": 3,
+                "is_closed": false,
+                "is_anonymous": true,
+                "type": "regular",
+                "allows_multiple_answers": false
+            },
+            "dice": {"emoji": "🎲", "value": 4},
+            "game": {
+                "title": "Beep",
+                "description": "Bop",
+                "photo": [{"file_id": "AgAD", "file_unique_id": "AQAD", "width": 90, "height": 90}]
+            },
+            "reply_markup": {
+                "inline_keyboard": [[
+                    {"text": "Vote", "callback_data": "vote"},
+                    {"text": "Login", "login_url": {"url": "https://example.com/login"}}
+                ]]
+            }
+        });
+
+        let message: Message = serde_json::from_value(json).expect("Invalid message");
+
+        let poll = message.poll.expect("Message without poll");
+        assert_eq!(poll.options[1].text, "Bop");
+        assert_eq!(message.dice.map(|dice| dice.value), Some(4));
+        assert_eq!(message.game.map(|game| game.photo.len()), Some(1));
+
+        let markup = message.reply_markup.expect("Message without reply markup");
+        assert_eq!(markup.inline_keyboard[0].len(), 2);
+    }
 }
@@ -0,0 +1,17 @@
+use serde_derive::Deserialize;
+
+use crate::object::User;
+
+/// A MessageEntity marks a special part of a text,
+/// like a hashtag or a bold section.
+///
+/// The offset and length are counted in UTF-16 code units.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MessageEntity {
+    pub r#type: String,
+    pub offset: usize,
+    pub length: usize,
+    pub url: Option<String>,
+    pub user: Option<User>,
+    pub language: Option<String>,
+}
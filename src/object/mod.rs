@@ -1,43 +1,87 @@
+pub mod animation;
+pub mod audio;
 pub mod callback_query;
 pub mod chat;
 pub mod chat_member;
 pub mod chat_photo;
 pub mod chosen_inline_result;
+pub mod contact;
+pub mod dice;
+pub mod document;
+pub mod encrypted_credentials;
+pub mod encrypted_passport_element;
 pub mod file_buffer;
 pub mod file_info;
+pub mod file_stream;
+pub mod game;
 pub mod inline_query;
+pub mod invoice;
 pub mod labeled_price;
 pub mod location;
+pub mod mask_position;
 pub mod message;
+pub mod message_entity;
 pub mod order_info;
+pub mod passport_data;
+pub mod passport_file;
 pub mod photo_size;
+pub mod poll;
+pub mod poll_option;
 pub mod precheckout_query;
 pub mod raw_message;
 pub mod shipping_address;
 pub mod shipping_query;
+pub mod sticker;
+pub mod successful_payment;
 pub mod update;
 pub mod update_kind;
 pub mod user;
 pub mod user_profile_photos;
+pub mod venue;
+pub mod video;
+pub mod video_note;
+pub mod voice;
 
+pub use animation::Animation;
+pub use audio::Audio;
 pub use callback_query::CallbackQuery;
 pub use chat::Chat;
 pub use chat_member::ChatMember;
 pub use chat_photo::ChatPhoto;
 pub use chosen_inline_result::ChosenInlineResult;
-pub use file_buffer::FileBuffer;
+pub use contact::Contact;
+pub use dice::Dice;
+pub use document::Document;
+pub use encrypted_credentials::EncryptedCredentials;
+pub use encrypted_passport_element::EncryptedPassportElement;
+pub use file_buffer::{FileBuffer, SaveOptions};
 pub use file_info::FileInfo;
+pub use file_stream::FileStream;
+pub use game::Game;
 pub use inline_query::InlineQuery;
+pub use invoice::Invoice;
 pub use labeled_price::LabeledPrice;
 pub use location::Location;
+pub use mask_position::MaskPosition;
 pub use message::Message;
+pub use message_entity::MessageEntity;
 pub use order_info::OrderInfo;
+pub use passport_data::PassportData;
+pub use passport_file::PassportFile;
 pub use photo_size::PhotoSize;
+pub use poll::Poll;
+pub use poll_option::PollOption;
 pub use precheckout_query::PrecheckoutQuery;
 pub use raw_message::RawMessage;
 pub use shipping_address::ShippingAddress;
 pub use shipping_query::ShippingQuery;
+pub use sticker::Sticker;
+pub use successful_payment::SuccessfulPayment;
 pub use update::Update;
-pub use update_kind::UpdateKind;
+pub use update_kind::{MalformedUpdate, UpdateKind};
 pub use user::User;
 pub use user_profile_photos::UserProfilePhotos;
+pub use venue::Venue;
+pub use video::Video;
+pub use video_note::VideoNote;
+pub use voice::Voice;
@@ -1,10 +1,11 @@
 use serde_derive::Deserialize;
 
-use crate::object::LabeledPrice;
+use crate::object::ShippingAddress;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct OrderInfo {
-    pub id: String,
-    pub title: String,
-    pub prices: Vec<LabeledPrice>,
+    pub name: Option<String>,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub shipping_address: Option<ShippingAddress>,
 }
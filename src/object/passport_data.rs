@@ -0,0 +1,10 @@
+use serde_derive::Deserialize;
+
+use crate::object::{EncryptedCredentials, EncryptedPassportElement};
+
+/// The Telegram Passport data a user shared with the bot.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PassportData {
+    pub data: Vec<EncryptedPassportElement>,
+    pub credentials: EncryptedCredentials,
+}
@@ -0,0 +1,9 @@
+use serde_derive::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PassportFile {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub file_size: u64,
+    pub file_date: i64,
+}
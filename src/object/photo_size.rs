@@ -1,8 +1,9 @@
 use serde_derive::Deserialize;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct PhotoSize {
     pub file_id: String,
+    pub file_unique_id: String,
     pub width: u32,
     pub height: u32,
     pub file_size: Option<usize>,
@@ -0,0 +1,24 @@
+use serde_derive::Deserialize;
+
+use crate::object::{MessageEntity, PollOption};
+
+/// A Poll, either a regular poll or a quiz.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Poll {
+    pub id: String,
+    pub question: String,
+    pub options: Vec<PollOption>,
+    pub total_voter_count: u32,
+    pub is_closed: bool,
+    pub is_anonymous: bool,
+    /// Either "regular" or "quiz".
+    pub r#type: String,
+    pub allows_multiple_answers: bool,
+    /// The index of the right option of a quiz, only known
+    /// once the quiz is closed or it was sent by the bot.
+    pub correct_option_id: Option<usize>,
+    pub explanation: Option<String>,
+    pub explanation_entities: Option<Vec<MessageEntity>>,
+    pub open_period: Option<u32>,
+    pub close_date: Option<i64>,
+}
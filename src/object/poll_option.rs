@@ -0,0 +1,7 @@
+use serde_derive::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PollOption {
+    pub text: String,
+    pub voter_count: u32,
+}
@@ -9,6 +9,6 @@ pub struct PrecheckoutQuery {
     pub currency: String,
     pub total_amount: u64,
     pub invoice_payload: String,
-    pub shipping_option_id: String,
-    pub order_info: Box<OrderInfo>,
+    pub shipping_option_id: Option<String>,
+    pub order_info: Option<Box<OrderInfo>>,
 }
@@ -1,12 +1,13 @@
 use serde_derive::Deserialize;
 
-use crate::object::User;
+use crate::object::{Chat, User};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct RawMessage {
     pub message_id: i64,
     pub date: i64,
-    pub from: User,
+    pub from: Option<User>,
+    pub sender_chat: Option<Box<Chat>>,
     pub text: Option<String>,
     pub new_chat_members: Option<Vec<User>>,
 }
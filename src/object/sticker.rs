@@ -0,0 +1,17 @@
+use serde_derive::Deserialize;
+
+use crate::object::{MaskPosition, PhotoSize};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Sticker {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub width: u32,
+    pub height: u32,
+    pub is_animated: bool,
+    pub thumb: Option<PhotoSize>,
+    pub emoji: Option<String>,
+    pub set_name: Option<String>,
+    pub mask_position: Option<MaskPosition>,
+    pub file_size: Option<u64>,
+}
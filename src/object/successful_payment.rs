@@ -0,0 +1,14 @@
+use serde_derive::Deserialize;
+
+use crate::object::OrderInfo;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SuccessfulPayment {
+    pub currency: String,
+    pub total_amount: u64,
+    pub invoice_payload: String,
+    pub shipping_option_id: Option<String>,
+    pub order_info: Option<Box<OrderInfo>>,
+    pub telegram_payment_charge_id: String,
+    pub provider_payment_charge_id: String,
+}
@@ -1,10 +1,228 @@
-use serde_derive::Deserialize;
+use serde::de::{Deserialize, Deserializer};
+use serde_json::Value;
 
-use crate::object::UpdateKind;
+use crate::object::{MalformedUpdate, UpdateKind};
 
-#[derive(Clone, Debug, Deserialize)]
+/// An Update received from telegram.
+///
+/// Updates without a valid update_id are kept as Malformed
+/// with an update_id of 0, so they never break a batch.
+#[derive(Clone, Debug)]
 pub struct Update {
     pub update_id: i64,
-    #[serde(flatten)]
     pub data: UpdateKind,
 }
+
+impl<'de> Deserialize<'de> for Update {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut fields = match Value::deserialize(deserializer)? {
+            Value::Object(fields) => fields,
+            data => return Ok(Update::malformed("Update is not an object", data)),
+        };
+
+        match fields.get("update_id").and_then(Value::as_i64) {
+            Some(update_id) => {
+                fields.remove("update_id");
+
+                Ok(Update {
+                    update_id,
+                    data: UpdateKind::from_fields(fields),
+                })
+            }
+            None => Ok(Update::malformed(
+                "Missing or invalid update_id",
+                Value::Object(fields),
+            )),
+        }
+    }
+}
+
+impl Update {
+    fn malformed(error: &str, data: Value) -> Self {
+        Update {
+            update_id: 0,
+            data: UpdateKind::Malformed(MalformedUpdate {
+                kind: "update".to_string(),
+                error: error.to_string(),
+                data,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod update_tests {
+    use crate::object::{Update, UpdateKind};
+
+    fn update_fixture(json: &str) -> UpdateKind {
+        let update: Update = serde_json::from_str(json).expect("Invalid update fixture");
+
+        update.data
+    }
+
+    #[test]
+    fn message() {
+        let kind = update_fixture(include_str!("../../res/updates/message.json"));
+
+        match kind {
+            UpdateKind::Message(message) => assert!(message.from.is_some()),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn edited_message() {
+        let kind = update_fixture(include_str!("../../res/updates/edited_message.json"));
+
+        match kind {
+            UpdateKind::EditedMessage(message) => assert!(message.edit_date.is_some()),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn channel_post() {
+        let kind = update_fixture(include_str!("../../res/updates/channel_post.json"));
+
+        match kind {
+            UpdateKind::ChannelPost(message) => {
+                assert!(message.from.is_none());
+                assert!(message.sender_chat.is_some());
+            }
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn edited_channel_post() {
+        let kind = update_fixture(include_str!("../../res/updates/edited_channel_post.json"));
+
+        match kind {
+            UpdateKind::EditChannelPost(message) => assert!(message.from.is_none()),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn inline_query() {
+        let kind = update_fixture(include_str!("../../res/updates/inline_query.json"));
+
+        match kind {
+            UpdateKind::InlineQuery(query) => assert_eq!(query.query, "puppy"),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn chosen_inline_result() {
+        let kind = update_fixture(include_str!("../../res/updates/chosen_inline_result.json"));
+
+        match kind {
+            UpdateKind::ChosenInlineResult(result) => assert!(result.location.is_some()),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn callback_query() {
+        let kind = update_fixture(include_str!("../../res/updates/callback_query.json"));
+
+        match kind {
+            UpdateKind::CallbackQuery(query) => assert_eq!(query.data, Some("yes".to_string())),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn shipping_query() {
+        let kind = update_fixture(include_str!("../../res/updates/shipping_query.json"));
+
+        match kind {
+            UpdateKind::ShippingQuery(query) => assert_eq!(query.invoice_payload, "order-1"),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn pre_checkout_query() {
+        let kind = update_fixture(include_str!("../../res/updates/pre_checkout_query.json"));
+
+        match kind {
+            UpdateKind::PrecheckoutQuery(query) => {
+                assert_eq!(query.shipping_option_id, Some("express".to_string()))
+            }
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn mixed_batch() {
+        let batch = format!(
+            "[{}, {}, {}]",
+            include_str!("../../res/updates/message.json"),
+            include_str!("../../res/updates/channel_post.json"),
+            include_str!("../../res/updates/callback_query.json"),
+        );
+
+        let updates: Vec<Update> = serde_json::from_str(&batch).expect("Invalid update batch");
+
+        assert_eq!(updates.len(), 3);
+    }
+
+    #[test]
+    fn unknown_kind() {
+        let kind = update_fixture(include_str!("../../res/updates/my_chat_member.json"));
+
+        match kind {
+            UpdateKind::Unknown(data) => assert!(data.get("my_chat_member").is_some()),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_update_in_batch() {
+        let batch = format!(
+            "[{}, {}, {}]",
+            include_str!("../../res/updates/message.json"),
+            r#"{"update_id": 483920111, "message": {"message_id": 1}}"#,
+            include_str!("../../res/updates/my_chat_member.json"),
+        );
+
+        let updates: Vec<Update> = serde_json::from_str(&batch).expect("Invalid update batch");
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[1].update_id, 483920111);
+
+        match &updates[1].data {
+            UpdateKind::Malformed(malformed) => assert_eq!(malformed.kind, "message"),
+            other => panic!("Wrong update kind: {:#?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_update_ids_in_batch() {
+        let batch = format!(
+            "[{}, {}, {}, {}]",
+            include_str!("../../res/updates/message.json"),
+            r#"{"message": {"message_id": 1}}"#,
+            r#"{"update_id": "483920112", "message": {"message_id": 1}}"#,
+            "null",
+        );
+
+        let updates: Vec<Update> = serde_json::from_str(&batch).expect("Invalid update batch");
+
+        assert_eq!(updates.len(), 4);
+
+        for update in &updates[1..] {
+            assert_eq!(update.update_id, 0);
+
+            match &update.data {
+                UpdateKind::Malformed(malformed) => assert_eq!(malformed.kind, "update"),
+                other => panic!("Wrong update kind: {:#?}", other),
+            }
+        }
+    }
+}
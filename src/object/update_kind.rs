@@ -1,27 +1,90 @@
-use serde_derive::Deserialize;
+use serde::de::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
 
 use crate::object::{
     CallbackQuery, ChosenInlineResult, InlineQuery, Message, PrecheckoutQuery, ShippingQuery,
 };
 
-#[derive(Clone, Debug, Deserialize)]
+/// The UpdateKind is the content of an Update.
+///
+/// Update kinds this crate does not know yet are kept as
+/// Unknown, and known kinds that fail to deserialize are
+/// kept as Malformed, so a single update never breaks a batch.
+#[derive(Clone, Debug)]
 pub enum UpdateKind {
-    #[serde(rename = "message")]
     Message(Message),
-    #[serde(rename = "edited_message")]
     EditedMessage(Message),
-    #[serde(rename = "channel_post")]
     ChannelPost(Message),
-    #[serde(rename = "edited_channel_post")]
     EditChannelPost(Message),
-    #[serde(rename = "inline_query")]
     InlineQuery(InlineQuery),
-    #[serde(rename = "chosen_inline_result")]
     ChosenInlineResult(ChosenInlineResult),
-    #[serde(rename = "callback_query")]
     CallbackQuery(CallbackQuery),
-    #[serde(rename = "shipping_query")]
     ShippingQuery(ShippingQuery),
-    #[serde(rename = "pre_checkout_query")]
     PrecheckoutQuery(PrecheckoutQuery),
+    /// An update kind not supported by this crate, with all
+    /// fields of the update except the update_id.
+    Unknown(Value),
+    /// A known update kind that could not be deserialized.
+    Malformed(MalformedUpdate),
+}
+
+/// The data of an update that could not be deserialized.
+#[derive(Clone, Debug)]
+pub struct MalformedUpdate {
+    pub kind: String,
+    pub error: String,
+    pub data: Value,
+}
+
+impl UpdateKind {
+    fn from_entry(kind: &str, data: &Value) -> Option<Result<UpdateKind, serde_json::Error>> {
+        let update_kind = match kind {
+            "message" => Message::deserialize(data).map(UpdateKind::Message),
+            "edited_message" => Message::deserialize(data).map(UpdateKind::EditedMessage),
+            "channel_post" => Message::deserialize(data).map(UpdateKind::ChannelPost),
+            "edited_channel_post" => Message::deserialize(data).map(UpdateKind::EditChannelPost),
+            "inline_query" => InlineQuery::deserialize(data).map(UpdateKind::InlineQuery),
+            "chosen_inline_result" => {
+                ChosenInlineResult::deserialize(data).map(UpdateKind::ChosenInlineResult)
+            }
+            "callback_query" => CallbackQuery::deserialize(data).map(UpdateKind::CallbackQuery),
+            "shipping_query" => ShippingQuery::deserialize(data).map(UpdateKind::ShippingQuery),
+            "pre_checkout_query" => {
+                PrecheckoutQuery::deserialize(data).map(UpdateKind::PrecheckoutQuery)
+            }
+            _ => return None,
+        };
+
+        Some(update_kind)
+    }
+
+    /// The kind of the fields of an update, except its update_id.
+    pub(crate) fn from_fields(fields: Map<String, Value>) -> UpdateKind {
+        for (kind, data) in fields.iter() {
+            match UpdateKind::from_entry(kind, data) {
+                Some(Ok(update_kind)) => return update_kind,
+                Some(Err(err)) => {
+                    return UpdateKind::Malformed(MalformedUpdate {
+                        kind: kind.to_owned(),
+                        error: err.to_string(),
+                        data: data.to_owned(),
+                    })
+                }
+                None => (),
+            }
+        }
+
+        UpdateKind::Unknown(Value::Object(fields))
+    }
+}
+
+impl<'de> Deserialize<'de> for UpdateKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields: Map<String, Value> = Map::deserialize(deserializer)?;
+
+        Ok(UpdateKind::from_fields(fields))
+    }
 }
@@ -0,0 +1,12 @@
+use serde_derive::Deserialize;
+
+use crate::object::Location;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Venue {
+    pub location: Location,
+    pub title: String,
+    pub address: String,
+    pub foursquare_id: Option<String>,
+    pub foursquare_type: Option<String>,
+}
@@ -0,0 +1,15 @@
+use serde_derive::Deserialize;
+
+use crate::object::PhotoSize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Video {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub width: u32,
+    pub height: u32,
+    pub duration: u32,
+    pub thumb: Option<PhotoSize>,
+    pub mime_type: Option<String>,
+    pub file_size: Option<u64>,
+}
@@ -0,0 +1,13 @@
+use serde_derive::Deserialize;
+
+use crate::object::PhotoSize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VideoNote {
+    pub file_id: String,
+    pub file_unique_id: String,
+    pub length: u32,
+    pub duration: u32,
+    pub thumb: Option<PhotoSize>,
+    pub file_size: Option<u64>,
+}
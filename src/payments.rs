@@ -0,0 +1,379 @@
+//! The Payments module provides utilities for
+//! driving a payment flow, from sending an invoice
+//! to receiving the successful payment.
+//!
+//! Pending orders are kept on an OrderStore keyed by
+//! the invoice payload, so shipping and pre-checkout
+//! queries can be validated against what was invoiced.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::bot::{Bot, BotResult};
+use crate::input::{AnswerPrecheckoutQuery, AnswerShippingQuery, SendInvoice, ShippingOption};
+use crate::object::{Message, PrecheckoutQuery, ShippingQuery, SuccessfulPayment};
+
+/// An Order is the expected outcome of an invoice,
+/// what the user must pay for the invoice payload.
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub invoice_payload: String,
+    pub currency: String,
+    pub total_amount: u64,
+    /// The shipping options offered for this order,
+    /// filled when answering a shipping query.
+    pub shipping_options: Vec<ShippingOption>,
+}
+
+impl Order {
+    pub fn new<S: Into<String>>(invoice_payload: S, currency: S, total_amount: u64) -> Self {
+        Order {
+            invoice_payload: invoice_payload.into(),
+            currency: currency.into(),
+            total_amount,
+            shipping_options: Vec::new(),
+        }
+    }
+
+    /// Create an order with the total of all prices of an invoice.
+    pub fn from_invoice(send_invoice: &SendInvoice) -> Self {
+        let total_amount = send_invoice.prices.iter().map(|price| price.amount).sum();
+
+        Order::new(
+            send_invoice.payload.as_str(),
+            send_invoice.currency.as_str(),
+            total_amount,
+        )
+    }
+
+    /// The amount expected for this order, shipping included.
+    ///
+    /// Returns None if the shipping option was never offered.
+    pub fn expected_amount(&self, shipping_option_id: Option<&str>) -> Option<u64> {
+        match shipping_option_id {
+            Some(id) => self
+                .shipping_options
+                .iter()
+                .find(|option| option.id == id)
+                .map(|option| {
+                    let shipping: u64 = option.prices.iter().map(|price| price.amount).sum();
+
+                    self.total_amount + shipping
+                }),
+            None => Some(self.total_amount),
+        }
+    }
+}
+
+/// An OrderStore keeps pending orders by invoice payload.
+///
+/// The MemoryOrderStore is enough for a single process,
+/// other stores can be used to survive restarts.
+pub trait OrderStore: Send + Sync {
+    /// Insert an order, replacing any order with the same payload.
+    fn insert(&self, order: Order);
+    /// Get a pending order by its invoice payload.
+    fn get(&self, invoice_payload: &str) -> Option<Order>;
+    /// Remove a pending order by its invoice payload.
+    fn remove(&self, invoice_payload: &str) -> Option<Order>;
+}
+
+/// An OrderStore backed by an in memory HashMap.
+#[derive(Debug, Default)]
+pub struct MemoryOrderStore {
+    orders: Mutex<HashMap<String, Order>>,
+}
+
+impl MemoryOrderStore {
+    pub fn new() -> Self {
+        MemoryOrderStore::default()
+    }
+}
+
+impl OrderStore for MemoryOrderStore {
+    fn insert(&self, order: Order) {
+        if let Ok(mut orders) = self.orders.lock() {
+            orders.insert(order.invoice_payload.clone(), order);
+        }
+    }
+
+    fn get(&self, invoice_payload: &str) -> Option<Order> {
+        self.orders
+            .lock()
+            .ok()
+            .and_then(|orders| orders.get(invoice_payload).cloned())
+    }
+
+    fn remove(&self, invoice_payload: &str) -> Option<Order> {
+        self.orders
+            .lock()
+            .ok()
+            .and_then(|mut orders| orders.remove(invoice_payload))
+    }
+}
+
+/// The reason a pre-checkout query was refused,
+/// the Display text is sent back to the user.
+#[derive(Debug, PartialEq)]
+pub enum PaymentError {
+    OrderNotFound(String),
+    CurrencyMismatch { expected: String, found: String },
+    AmountMismatch { expected: u64, found: u64 },
+    UnknownShippingOption(String),
+}
+
+impl std::fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            PaymentError::OrderNotFound(_) => "This order is no longer available.".fmt(f),
+            PaymentError::CurrencyMismatch { .. } | PaymentError::AmountMismatch { .. } => {
+                "The price of this order has changed.".fmt(f)
+            }
+            PaymentError::UnknownShippingOption(_) => {
+                "The selected shipping option is not available.".fmt(f)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+/// The event emitted when a successful payment arrives.
+///
+/// The order is None when it was not found on the store,
+/// which can happen if the store was not persistent.
+#[derive(Clone, Debug)]
+pub struct PaymentCompleted {
+    pub order: Option<Order>,
+    pub payment: SuccessfulPayment,
+    pub message: Message,
+}
+
+type ShippingCallback =
+    dyn Fn(&ShippingQuery, &Order) -> Result<Vec<ShippingOption>, String> + Send + Sync;
+
+/// The PaymentFlow answers shipping and pre-checkout
+/// queries using the orders on its store.
+#[derive(Clone)]
+pub struct PaymentFlow {
+    store: Arc<dyn OrderStore>,
+    shipping_callback: Option<Arc<ShippingCallback>>,
+}
+
+impl std::fmt::Debug for PaymentFlow {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "PaymentFlow")
+    }
+}
+
+impl PaymentFlow {
+    pub fn new<S: OrderStore + 'static>(store: S) -> Self {
+        PaymentFlow {
+            store: Arc::new(store),
+            shipping_callback: None,
+        }
+    }
+
+    /// Set the callback used to answer shipping queries.
+    ///
+    /// An Err is sent back to the user as the reason
+    /// the order cannot be shipped.
+    pub fn with_shipping_options<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ShippingQuery, &Order) -> Result<Vec<ShippingOption>, String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.shipping_callback = Some(Arc::new(callback));
+
+        self
+    }
+
+    /// The store holding the pending orders.
+    pub fn store(&self) -> &dyn OrderStore {
+        self.store.as_ref()
+    }
+
+    /// Store the order of an invoice and send it.
+    pub async fn send_invoice(&self, bot: &Bot, send_invoice: SendInvoice) -> BotResult<Message> {
+        self.store.insert(Order::from_invoice(&send_invoice));
+
+        bot.send_invoice(send_invoice).await
+    }
+
+    /// Compute the answer for a shipping query, storing
+    /// the offered options on the order.
+    pub fn check_shipping(&self, query: &ShippingQuery) -> AnswerShippingQuery {
+        let mut order = match self.store.get(&query.invoice_payload) {
+            Some(order) => order,
+            None => {
+                let error = PaymentError::OrderNotFound(query.invoice_payload.clone());
+
+                return AnswerShippingQuery::error(query.id.clone(), error.to_string());
+            }
+        };
+
+        let shipping_options = match self.shipping_callback.as_ref() {
+            Some(callback) => callback(query, &order),
+            None => Err("Shipping is not available.".to_string()),
+        };
+
+        match shipping_options {
+            Ok(options) => {
+                order.shipping_options = options.clone();
+                self.store.insert(order);
+
+                AnswerShippingQuery::ok(query.id.clone(), options)
+            }
+            Err(message) => AnswerShippingQuery::error(query.id.clone(), message),
+        }
+    }
+
+    /// Answer a shipping query with the shipping options callback.
+    pub async fn answer_shipping_query(&self, bot: &Bot, query: &ShippingQuery) -> BotResult<bool> {
+        bot.answer_shipping_query(self.check_shipping(query)).await
+    }
+
+    /// Validate a pre-checkout query against its stored order.
+    pub fn check_precheckout(&self, query: &PrecheckoutQuery) -> Result<Order, PaymentError> {
+        let order = self
+            .store
+            .get(&query.invoice_payload)
+            .ok_or_else(|| PaymentError::OrderNotFound(query.invoice_payload.clone()))?;
+
+        if order.currency != query.currency {
+            return Err(PaymentError::CurrencyMismatch {
+                expected: order.currency,
+                found: query.currency.clone(),
+            });
+        }
+
+        let shipping_option_id = query.shipping_option_id.as_deref();
+
+        let expected = order.expected_amount(shipping_option_id).ok_or_else(|| {
+            PaymentError::UnknownShippingOption(shipping_option_id.unwrap_or("").to_string())
+        })?;
+
+        if expected != query.total_amount {
+            return Err(PaymentError::AmountMismatch {
+                expected,
+                found: query.total_amount,
+            });
+        }
+
+        Ok(order)
+    }
+
+    /// Compute the answer for a pre-checkout query,
+    /// refusing it with the reason the check failed.
+    pub fn precheckout_answer(&self, query: &PrecheckoutQuery) -> AnswerPrecheckoutQuery {
+        match self.check_precheckout(query) {
+            Ok(_) => AnswerPrecheckoutQuery::ok(query.id.clone()),
+            Err(err) => AnswerPrecheckoutQuery::error(query.id.clone(), err.to_string()),
+        }
+    }
+
+    /// Validate and answer a pre-checkout query.
+    ///
+    /// Telegram expects this answer within 10 seconds,
+    /// so this should be called as soon as the query arrives.
+    pub async fn answer_precheckout_query(
+        &self,
+        bot: &Bot,
+        query: &PrecheckoutQuery,
+    ) -> BotResult<bool> {
+        bot.answer_precheckout_query(self.precheckout_answer(query))
+            .await
+    }
+
+    /// Complete the order of a message with a successful payment,
+    /// removing it from the store.
+    ///
+    /// Returns None for messages without a payment.
+    pub fn complete_payment(&self, message: &Message) -> Option<PaymentCompleted> {
+        message
+            .successful_payment
+            .as_ref()
+            .map(|payment| PaymentCompleted {
+                order: self.store.remove(&payment.invoice_payload),
+                payment: payment.clone(),
+                message: message.clone(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod payments_tests {
+    use super::*;
+    use crate::object::LabeledPrice;
+
+    fn precheckout_query(total_amount: u64, shipping_option_id: Option<&str>) -> PrecheckoutQuery {
+        let json = serde_json::json!({
+            "id": "query",
+            "from": {"id": 1, "is_bot": false, "first_name": "Buyer"},
+            "currency": "USD",
+            "total_amount": total_amount,
+            "invoice_payload": "order-1",
+            "shipping_option_id": shipping_option_id,
+        });
+
+        serde_json::from_value(json).expect("Invalid pre-checkout query")
+    }
+
+    fn payment_flow() -> PaymentFlow {
+        let flow = PaymentFlow::new(MemoryOrderStore::new());
+
+        let mut order = Order::new("order-1", "USD", 1000);
+        order.shipping_options = vec![ShippingOption::new(
+            "express",
+            "Express",
+            vec![LabeledPrice::new("Express", 500)],
+        )];
+
+        flow.store().insert(order);
+
+        flow
+    }
+
+    #[test]
+    fn precheckout_matching_order() {
+        let flow = payment_flow();
+
+        assert!(flow
+            .check_precheckout(&precheckout_query(1000, None))
+            .is_ok());
+        assert!(flow
+            .check_precheckout(&precheckout_query(1500, Some("express")))
+            .is_ok());
+    }
+
+    #[test]
+    fn precheckout_wrong_amount() {
+        let flow = payment_flow();
+
+        assert_eq!(
+            flow.check_precheckout(&precheckout_query(900, None))
+                .unwrap_err(),
+            PaymentError::AmountMismatch {
+                expected: 1000,
+                found: 900
+            }
+        );
+        assert_eq!(
+            flow.check_precheckout(&precheckout_query(1500, Some("slow")))
+                .unwrap_err(),
+            PaymentError::UnknownShippingOption("slow".to_string())
+        );
+    }
+
+    #[test]
+    fn precheckout_missing_order() {
+        let flow = PaymentFlow::new(MemoryOrderStore::new());
+
+        assert_eq!(
+            flow.check_precheckout(&precheckout_query(1000, None))
+                .unwrap_err(),
+            PaymentError::OrderNotFound("order-1".to_string())
+        );
+    }
+}
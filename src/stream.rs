@@ -6,21 +6,44 @@ use std::time::Duration;
 
 use tokio::runtime::Runtime;
 
-use crate::bot::Bot;
-use crate::error::StreamError;
-use crate::input::GetUpdates;
+use crate::bot::{Bot, BotResult};
+use crate::error::{BotError, StreamError};
+use crate::input::{AnswerPrecheckoutQuery, AnswerShippingQuery, GetUpdates};
+use crate::object::MalformedUpdate;
 use crate::object::Message;
 use crate::object::Update;
 use crate::object::UpdateKind;
+use crate::payments::{PaymentCompleted, PaymentFlow};
 
 #[cfg(feature = "stream-logging")]
-use log::info;
+use log::{info, warn};
 
 type MessageSender = mpsc::Sender<Message>;
+type PaymentSender = mpsc::Sender<PaymentCompleted>;
+type MalformedSender = mpsc::Sender<MalformedUpdate>;
+type ErrorHandler = Box<dyn Fn(&BotError) + Send>;
+
+/// The answer to a payment query, sent as soon as the query arrives.
+enum PaymentAnswer {
+    Shipping(AnswerShippingQuery),
+    Precheckout(AnswerPrecheckoutQuery),
+}
+
+impl PaymentAnswer {
+    async fn send(self, bot: &Bot) -> BotResult<bool> {
+        match self {
+            PaymentAnswer::Shipping(answer) => bot.answer_shipping_query(answer).await,
+            PaymentAnswer::Precheckout(answer) => bot.answer_precheckout_query(answer).await,
+        }
+    }
+}
 
 pub struct Stream {
     bot: Bot,
     message_sender: Option<MessageSender>,
+    payments: Option<(PaymentFlow, PaymentSender)>,
+    malformed_sender: Option<MalformedSender>,
+    error_handler: Option<ErrorHandler>,
 }
 
 impl Stream {
@@ -28,12 +51,15 @@ impl Stream {
         Stream {
             bot: bot.clone(),
             message_sender: None,
+            payments: None,
+            malformed_sender: None,
+            error_handler: None,
         }
     }
 
     pub fn for_each_message<Func>(&mut self, closure: Func)
     where
-        Func: Send + 'static + Fn(&mut Runtime, Bot, Message) -> (),
+        Func: Send + 'static + Fn(&Runtime, &Bot, Message),
     {
         let (sender, receiver) = mpsc::channel();
 
@@ -42,23 +68,80 @@ impl Stream {
         self.message_sender = Some(sender);
 
         thread::spawn(move || {
-            let mut runtime = Runtime::new().expect("Unable to create a runtime");
+            let runtime = Runtime::new().expect("Unable to create a runtime");
 
             for message in receiver {
                 #[cfg(feature = "stream-logging")]
                 info!("Message streamer received: {:?}", message);
 
-                closure(&mut runtime, thread_bot.clone(), message);
+                closure(&runtime, &thread_bot, message);
+            }
+        });
+    }
+
+    /// Answer shipping and pre-checkout queries with a PaymentFlow,
+    /// calling the closure for every completed payment.
+    ///
+    /// The queries are answered as they arrive, so they fit
+    /// within the time limits telegram imposes.
+    pub fn for_each_payment<Func>(&mut self, payment_flow: PaymentFlow, closure: Func)
+    where
+        Func: Send + 'static + Fn(&Runtime, &Bot, PaymentCompleted),
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let thread_bot = self.bot.clone();
+
+        self.payments = Some((payment_flow, sender));
+
+        thread::spawn(move || {
+            let runtime = Runtime::new().expect("Unable to create a runtime");
+
+            for payment in receiver {
+                #[cfg(feature = "stream-logging")]
+                info!("Payment streamer received: {:?}", payment);
+
+                closure(&runtime, &thread_bot, payment);
+            }
+        });
+    }
+
+    /// Call the closure for every update that could not be
+    /// deserialized, instead of silently skipping it.
+    pub fn for_each_malformed<Func>(&mut self, closure: Func)
+    where
+        Func: Send + 'static + Fn(&Runtime, &Bot, MalformedUpdate),
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let thread_bot = self.bot.clone();
+
+        self.malformed_sender = Some(sender);
+
+        thread::spawn(move || {
+            let runtime = Runtime::new().expect("Unable to create a runtime");
+
+            for malformed in receiver {
+                closure(&runtime, &thread_bot, malformed);
             }
         });
     }
 
+    /// Call the closure for every failed answer to a query,
+    /// the stream keeps running after a failed answer.
+    pub fn on_error<Func>(&mut self, closure: Func)
+    where
+        Func: Send + 'static + Fn(&BotError),
+    {
+        self.error_handler = Some(Box::new(closure));
+    }
+
     pub fn run(self) -> Result<(), StreamError> {
         #[cfg(feature = "stream-logging")]
         env_logger::init();
 
         let mut update_offset: Option<i64> = None;
-        let mut runtime = Runtime::new().expect("Unable to create a runtime");
+        let runtime = Runtime::new().expect("Unable to create a runtime");
 
         loop {
             let mut get_updates_args = GetUpdates::new();
@@ -67,22 +150,153 @@ impl Stream {
             #[cfg(feature = "stream-logging")]
             info!("Fetching {:?}", get_updates_args);
 
-            let (_, updates) =
-                runtime.block_on(self.bot.to_owned().get_updates(get_updates_args))?;
+            let updates = runtime.block_on(self.bot.get_updates(get_updates_args))?;
 
-            update_offset = updates
-                .last()
-                .map(|last_update: &Update| last_update.update_id + 1);
+            // Malformed updates without an update_id cannot confirm
+            // the batch, the offset follows the last valid one.
+            if let Some(last_id) = updates
+                .iter()
+                .map(|update: &Update| update.update_id)
+                .filter(|&update_id| update_id > 0)
+                .max()
+            {
+                update_offset = Some(last_id + 1);
+            }
 
             for update in updates {
-                if let (UpdateKind::Message(message), Some(sender)) =
-                    (update.data, self.message_sender.as_ref())
-                {
+                self.handle_update(update, |answer| runtime.block_on(answer.send(&self.bot)))?;
+            }
+
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    /// Dispatch an update, sending the answers to payment queries
+    /// with the answer closure and only failing when a channel is closed.
+    #[allow(clippy::result_large_err)]
+    fn handle_update<Answer>(&self, update: Update, answer: Answer) -> Result<(), StreamError>
+    where
+        Answer: Fn(PaymentAnswer) -> BotResult<bool>,
+    {
+        match (update.data, self.payments.as_ref()) {
+            (UpdateKind::ShippingQuery(query), Some((flow, _))) => {
+                self.report(answer(PaymentAnswer::Shipping(flow.check_shipping(&query))));
+            }
+            (UpdateKind::PrecheckoutQuery(query), Some((flow, _))) => {
+                self.report(answer(PaymentAnswer::Precheckout(
+                    flow.precheckout_answer(&query),
+                )));
+            }
+            (UpdateKind::Message(message), payments) => {
+                if let Some((flow, sender)) = payments {
+                    if let Some(payment) = flow.complete_payment(&message) {
+                        sender.send(payment)?;
+                    }
+                }
+
+                if let Some(sender) = self.message_sender.as_ref() {
                     sender.send(message)?;
                 }
             }
+            (UpdateKind::Malformed(malformed), _) => {
+                #[cfg(feature = "stream-logging")]
+                warn!(
+                    "Received malformed {} update: {}",
+                    malformed.kind, malformed.error
+                );
 
-            std::thread::sleep(Duration::from_secs(1));
+                if let Some(sender) = self.malformed_sender.as_ref() {
+                    sender.send(malformed)?;
+                }
+            }
+            _ => (),
         }
+
+        Ok(())
+    }
+
+    /// Report a failed answer, without stopping the stream.
+    fn report(&self, answer: BotResult<bool>) {
+        if let Err(err) = answer {
+            #[cfg(feature = "stream-logging")]
+            warn!("Failed to answer a query: {}", err);
+
+            if let Some(error_handler) = self.error_handler.as_ref() {
+                error_handler(&err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+    use crate::payments::MemoryOrderStore;
+    use std::sync::{Arc, Mutex};
+
+    fn update(json: serde_json::Value) -> Update {
+        serde_json::from_value(json).expect("Invalid update")
+    }
+
+    /// An answer failing the way telegram refuses a late answer.
+    fn failed_answer(_: PaymentAnswer) -> BotResult<bool> {
+        Err(BotError::TelegramError(
+            "Bad Request: query is too old".to_string(),
+        ))
+    }
+
+    #[test]
+    fn continue_after_failed_answers() {
+        let (message_sender, messages) = mpsc::channel();
+        let (payment_sender, _payments) = mpsc::channel();
+        let errors = Arc::new(Mutex::new(0));
+        let handler_errors = errors.clone();
+
+        let mut stream = Stream::new(&Bot::new("TOKEN"));
+        stream.message_sender = Some(message_sender);
+        stream.payments = Some((PaymentFlow::new(MemoryOrderStore::new()), payment_sender));
+        stream.on_error(move |_| *handler_errors.lock().unwrap() += 1);
+
+        let precheckout = update(serde_json::json!({
+            "update_id": 1,
+            "pre_checkout_query": {
+                "id": "query",
+                "from": {"id": 1, "is_bot": false, "first_name": "Buyer"},
+                "currency": "USD",
+                "total_amount": 1000,
+                "invoice_payload": "order-1",
+            },
+        }));
+        let message = update(serde_json::json!({
+            "update_id": 2,
+            "message": {
+                "message_id": 1,
+                "date": 0,
+                "chat": {"id": 1, "type": "private"},
+                "text": "beep",
+            },
+        }));
+
+        assert!(stream.handle_update(precheckout, failed_answer).is_ok());
+        assert!(stream.handle_update(message, failed_answer).is_ok());
+
+        assert_eq!(*errors.lock().unwrap(), 1);
+        assert_eq!(messages.try_recv().unwrap().text, Some("beep".to_string()));
+    }
+
+    #[test]
+    fn send_malformed_updates() {
+        let (malformed_sender, malformed) = mpsc::channel();
+
+        let mut stream = Stream::new(&Bot::new("TOKEN"));
+        stream.malformed_sender = Some(malformed_sender);
+
+        let broken = update(serde_json::json!({
+            "update_id": 1,
+            "message": {"message_id": "not a number"},
+        }));
+
+        assert!(stream.handle_update(broken, failed_answer).is_ok());
+        assert_eq!(malformed.try_recv().unwrap().kind, "message");
     }
 }
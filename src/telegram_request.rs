@@ -2,124 +2,196 @@
 //! The TelegramRequest module is responsible for
 //! wrapping telegram responses into concrete objects and
 //! constructing requests to query Telegram.
+//!
+//! The arguments of a request are kept as json fields and
+//! encoded when the request is executed: as a json body,
+//! as a multipart form if there are files to upload or as
+//! a query string if the bot is set to use GET requests.
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::{Map, Value};
 
-use futures::Future;
-
-use reqwest::r#async::multipart::Form;
-use reqwest::r#async::multipart::Part;
-use reqwest::r#async::RequestBuilder;
-use reqwest::r#async::Response;
+use reqwest::multipart::Form;
+use reqwest::multipart::Part;
+use reqwest::Client;
+use reqwest::RequestBuilder;
+use reqwest::StatusCode;
 
 use crate::bot::{Bot, BotResult};
 use crate::error::BotError;
+use crate::formatting::check_caption;
 use crate::input::Uploader;
 
-/// An HTTP method abstraction enum
-pub(crate) enum Method {
-    /// GET HTTP method variant
-    GET,
-    /// POST HTTP method variant
-    POST,
+/// How the fields of a request are sent to telegram.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Encoding {
+    /// A json body on a POST request
+    Json,
+    /// A multipart form on a POST request, for uploads
+    Multipart,
+    /// A query string on a GET request, for debugging
+    Query,
 }
 
 /// A struct encoding a telegram request created by a particular bot.
 pub(crate) struct TelegramRequest {
-    builder: RequestBuilder,
+    route: String,
+    fields: Map<String, Value>,
     form: Option<Form>,
-    bot: Bot,
+    error: Option<BotError>,
+    client: Client,
+    get_requests: bool,
 }
 
 impl TelegramRequest {
-    /// Create a new telegram request with a method and a URI.
-    pub(crate) fn new(method: Method, route: String, bot: Bot) -> Self {
-        let client = &bot.connection.client;
-
-        let request = match method {
-            Method::GET => client.get(&route),
-            Method::POST => client.post(&route),
-        };
-
+    /// Create a new telegram request to a route.
+    pub(crate) fn new(route: String, bot: &Bot) -> Self {
         TelegramRequest {
-            builder: request,
+            route,
+            fields: Map::new(),
             form: None,
-            bot,
+            error: None,
+            client: bot.connection.client.clone(),
+            get_requests: bot.connection.get_requests,
         }
     }
 
-    /// Append a json body to the request
-    pub(crate) fn with_body<B: Serialize + Sized>(mut self, body_data: B) -> Self {
-        self.builder = self.builder.json(&body_data);
+    /// Append all fields of an input to the request,
+    /// refusing captions over the telegram limit.
+    pub(crate) fn with_input<I: Serialize>(mut self, input: I) -> Self {
+        match serde_json::to_value(input) {
+            Ok(Value::Object(fields)) => {
+                if let Some(caption) = fields.get("caption").and_then(Value::as_str) {
+                    let parse_mode = fields
+                        .get("parse_mode")
+                        .and_then(|parse_mode| serde_json::from_value(parse_mode.clone()).ok());
 
-        self
-    }
+                    if let Err(err) = check_caption(caption, parse_mode) {
+                        self.error = Some(err);
+                    }
+                }
 
-    /// Append an optional form into this request
-    fn with_optional_form(mut self) -> Self {
-        if let Some(form) = self.form.take() {
-            self.builder = self.builder.multipart(form);
+                self.fields.extend(fields)
+            }
+            Ok(_) => {
+                self.error = Some(BotError::EncodeError(
+                    "Request inputs must be encoded as json objects.".to_string(),
+                ))
+            }
+            Err(err) => self.error = Some(BotError::EncodeError(err.to_string())),
         }
 
         self
     }
 
-    /// Append a query to the request
-    pub(crate) fn with_query<Q: Serialize + Sized>(mut self, query_data: Q) -> Self {
-        self.builder = self.builder.query(&query_data);
+    /// Append a single field to the request
+    pub(crate) fn with_field<V: Serialize>(mut self, tag: &str, value: V) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.fields.insert(tag.to_owned(), value);
+            }
+            Err(err) => self.error = Some(BotError::EncodeError(err.to_string())),
+        }
 
         self
     }
 
     /// Append a form part to the request
     pub(crate) fn with_form_part(mut self, tag: &str, part: Part) -> Self {
-        if let Some(form) = self.form.take() {
-            self.form = Some(form.part(tag.to_owned(), part));
-        } else {
-            let form = Form::new();
+        let form = self.form.take().unwrap_or_default();
 
-            self.form = Some(form.part(tag.to_owned(), part));
-        }
+        self.form = Some(form.part(tag.to_owned(), part));
 
         self
     }
 
-    /// Append a textual form to the request
-    pub(crate) fn with_form_text<S: ToString>(mut self, tag: S, text: S) -> Self {
-        if let Some(form) = self.form.take() {
-            self.form = Some(form.text(tag.to_string(), text.to_string()));
-        } else {
-            let form = Form::new();
+    /// Inject an Uploader object into this request
+    pub(crate) fn with_uploader(self, tag: &str, uploader: Uploader) -> Self {
+        uploader.upload_into(tag, self)
+    }
 
-            self.form = Some(form.text(tag.to_string(), text.to_string()));
+    /// The encoding used to send this request.
+    pub(crate) fn encoding(&self) -> Encoding {
+        if self.form.is_some() {
+            Encoding::Multipart
+        } else if self.get_requests {
+            Encoding::Query
+        } else {
+            Encoding::Json
         }
+    }
 
-        self
+    /// The fields as text, nested fields are json encoded
+    /// and empty fields are left out.
+    fn text_fields(fields: Map<String, Value>) -> Vec<(String, String)> {
+        fields
+            .into_iter()
+            .filter_map(|(tag, value)| match value {
+                Value::Null => None,
+                Value::String(text) => Some((tag, text)),
+                value => Some((tag, value.to_string())),
+            })
+            .collect()
     }
 
-    /// Inject an Uploader object into this request
-    pub(crate) fn with_uploader(self, tag: &str, uploader: Uploader) -> Self {
-        uploader.upload_into(tag, self)
+    /// Build the HTTP request with the fields encoded.
+    fn into_builder(mut self) -> Result<RequestBuilder, BotError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let encoding = self.encoding();
+        let client = &self.client;
+
+        let builder = match encoding {
+            Encoding::Json => {
+                let fields: Map<String, Value> = self
+                    .fields
+                    .into_iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .collect();
+
+                client.post(&self.route).json(&fields)
+            }
+            Encoding::Multipart => {
+                let form = Self::text_fields(self.fields)
+                    .into_iter()
+                    .fold(self.form.unwrap_or_default(), |form, (tag, text)| {
+                        form.text(tag, text)
+                    });
+
+                client.post(&self.route).multipart(form)
+            }
+            Encoding::Query => client
+                .get(&self.route)
+                .query(&Self::text_fields(self.fields)),
+        };
+
+        Ok(builder)
     }
 
-    /// Execute this request returning a Future
-    pub(crate) fn execute<O: DeserializeOwned + std::fmt::Debug>(
-        mut self,
-    ) -> impl Future<Item = (Bot, O), Error = BotError> {
-        self = self.with_optional_form();
-        let bot = self.bot;
+    /// Execute this request, decoding the result
+    pub(crate) async fn execute<O: DeserializeOwned + std::fmt::Debug>(self) -> BotResult<O> {
+        let response = self.into_builder()?.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
 
-        self.builder
-            .send()
-            .and_then(|mut response: Response| response.json())
-            .map_err(BotError::from)
-            .and_then(|api_response: TelegramResponse<O>| {
-                let api_result: BotResult<O> = api_response.into();
+        decode_response(status, &body)
+    }
+}
 
-                api_result
-            })
-            .map(move |data: O| (bot, data))
+/// Decode the body of a response to a telegram request.
+///
+/// Bodies which are not telegram json, like the pages of
+/// proxies, are errors with the HTTP status of the response.
+fn decode_response<O>(status: StatusCode, body: &[u8]) -> BotResult<O>
+where
+    O: DeserializeOwned + std::fmt::Debug,
+{
+    match serde_json::from_slice::<TelegramResponse<O>>(body) {
+        Ok(api_response) => api_response.into(),
+        Err(err) => Err(BotError::InvalidResponse(status.as_u16(), err.to_string())),
     }
 }
 
@@ -138,7 +210,7 @@ where
 impl<T: std::fmt::Debug> Into<BotResult<T>> for TelegramResponse<T> {
     fn into(self) -> BotResult<T> {
         if self.ok {
-            Ok(self.result.expect("Ok response must have data."))
+            self.result.ok_or(BotError::EmptyResult)
         } else {
             let mut error_message = String::new();
             error_message.push_str(&"Telegram(");
@@ -156,3 +228,219 @@ impl<T: std::fmt::Debug> Into<BotResult<T>> for TelegramResponse<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod telegram_request_tests {
+    use super::*;
+    use crate::input::*;
+    use crate::object::FileStream;
+    use crate::upload_cache::MediaKind;
+    use serde_json::{json, Value};
+
+    fn request() -> TelegramRequest {
+        let bot = Bot::new("TOKEN");
+
+        TelegramRequest::new(bot.get_route("test"), &bot)
+    }
+
+    /// The decoded query pairs of a GET request with this input.
+    fn query_pairs<Q: Serialize>(query: Q) -> Vec<(String, String)> {
+        let bot = Bot::new("TOKEN").with_get_requests();
+
+        let builder = TelegramRequest::new(bot.get_route("test"), &bot)
+            .with_input(query)
+            .into_builder()
+            .expect("Invalid input");
+
+        let request = builder.build().expect("Invalid request");
+
+        request.url().query_pairs().into_owned().collect()
+    }
+
+    fn query_json(pairs: &[(String, String)], key: &str) -> Option<Value> {
+        pairs
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| serde_json::from_str(value).expect("Field is not json"))
+    }
+
+    #[test]
+    fn query_reply_markup() {
+        let markup = InlineKeyboard::new()
+            .row(|r| r.callback("Yes", "y"))
+            .finish()
+            .unwrap();
+
+        let pairs = query_pairs(SendMessage::new(1, "Hello".to_string()).with_reply_markup(markup));
+
+        assert!(pairs.contains(&("text".to_string(), "Hello".to_string())));
+        assert!(!pairs.iter().any(|(name, _)| name == "inline_keyboard"));
+        assert_eq!(
+            query_json(&pairs, "reply_markup"),
+            Some(json!({"inline_keyboard": [[{"text": "Yes", "callback_data": "y"}]]}))
+        );
+    }
+
+    #[test]
+    fn query_without_reply_markup() {
+        let pairs = query_pairs(SendLocation::new(1, 1.5, -2.5));
+
+        assert!(pairs.contains(&("latitude".to_string(), "1.5".to_string())));
+        assert_eq!(query_json(&pairs, "reply_markup"), None);
+    }
+
+    #[test]
+    fn upload_query_reply_markup() {
+        let markup = ReplyKeyboard::new()
+            .row(|r| r.text("Hi"))
+            .resize()
+            .finish()
+            .unwrap();
+
+        let (query, _) = SendPhoto::new(1, file_id("photo"))
+            .with_reply_markup(markup)
+            .split();
+
+        assert_eq!(
+            query_json(&query_pairs(query), "reply_markup"),
+            Some(json!({
+                "keyboard": [[{"text": "Hi", "request_contact": false, "request_location": false}]],
+                "resize_keyboard": true,
+                "one_time_keyboard": false,
+                "selective": false,
+            }))
+        );
+    }
+
+    #[test]
+    fn body_reply_markup() {
+        let markup = ForceReply::default();
+        let body = serde_json::to_value(SendContact::default().with_reply_markup(markup)).unwrap();
+
+        // Json bodies keep the markup nested, it is only
+        // encoded as a string for query strings and forms.
+        assert_eq!(
+            body["reply_markup"],
+            json!({"force_reply": true, "selective": false})
+        );
+    }
+
+    #[test]
+    fn json_by_default() {
+        let request = request()
+            .with_input(SendMessage::new(1, "Hello".to_string()))
+            .with_uploader("photo", file_id("photo").into());
+
+        assert_eq!(request.encoding(), Encoding::Json);
+        assert_eq!(request.fields["text"], json!("Hello"));
+        assert_eq!(request.fields["photo"], json!("photo"));
+    }
+
+    #[test]
+    fn multipart_for_files() {
+        let uploader = FileUploader::new("res/puppy.jpg").expect("Missing file");
+        let request = request()
+            .with_input(SendMessage::new(1, "Hello".to_string()))
+            .with_uploader("photo", uploader.into());
+
+        assert_eq!(request.encoding(), Encoding::Multipart);
+        assert!(!request.fields.contains_key("photo"));
+    }
+
+    /// A request transferring a downloaded file of some size.
+    fn transfer_request(size: usize) -> TelegramRequest {
+        let chunks = futures::stream::iter(vec![Ok(bytes::Bytes::from(vec![0; size]))]);
+        let file_stream = FileStream::new(
+            "documents/file_1.pdf".to_string(),
+            Some(size as u64),
+            chunks,
+        );
+
+        let (query, kind) = TransferTarget::new(1, MediaKind::Document)
+            .with_reply_to_message_id(42)
+            .split();
+
+        request().with_input(query).with_uploader(
+            kind.field(),
+            FileUploader::from_file_stream(file_stream).into(),
+        )
+    }
+
+    #[test]
+    fn multipart_from_file_stream() {
+        let request = transfer_request(10);
+
+        assert_eq!(request.encoding(), Encoding::Multipart);
+        assert_eq!(request.fields["reply_to_message_id"], json!(42));
+
+        let form = format!("{:?}", request.form);
+        assert!(form.contains("(\"document\", Part"));
+        assert!(form.contains("file_name: Some(\"documents_file_1.pdf\")"));
+
+        // The size is known, so the length of the body is sent.
+        let length = |request: TelegramRequest| -> u64 {
+            let request = request.into_builder().unwrap().build().unwrap();
+
+            request.headers()[reqwest::header::CONTENT_LENGTH]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+
+        assert_eq!(length(transfer_request(1010)) - length(request), 1000);
+    }
+
+    #[test]
+    fn caption_limit() {
+        let fits = SendPhoto::new(1, file_id("photo"))
+            .with_caption(format!("<b>{}</b>", "a".repeat(1024)))
+            .with_parse_mode(ParseMode::Html)
+            .split()
+            .0;
+        let over = SendPhoto::new(1, file_id("photo"))
+            .with_caption("a".repeat(1025))
+            .split()
+            .0;
+
+        assert!(request().with_input(fits).error.is_none());
+
+        match request().with_input(over).error {
+            Some(BotError::CaptionTooLong(length)) => assert_eq!(length, 1025),
+            other => panic!("Expected a long caption error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_responses() {
+        let message: BotResult<bool> =
+            decode_response(StatusCode::OK, br#"{"ok":true,"result":true}"#);
+        assert!(message.unwrap());
+
+        let error: BotResult<bool> = decode_response(
+            StatusCode::BAD_REQUEST,
+            br#"{"ok":false,"error_code":400,"description":"Bad Request"}"#,
+        );
+        assert!(matches!(error, Err(BotError::TelegramError(_))));
+
+        let empty: BotResult<bool> = decode_response(StatusCode::OK, br#"{"ok":true}"#);
+        assert!(matches!(empty, Err(BotError::EmptyResult)));
+
+        let page: BotResult<bool> = decode_response(StatusCode::BAD_GATEWAY, b"<html>502</html>");
+        assert!(matches!(page, Err(BotError::InvalidResponse(502, _))));
+    }
+
+    #[test]
+    fn single_fields() {
+        let pairs = query_pairs(json!({"chat_id": 5}));
+        assert_eq!(pairs, vec![("chat_id".to_string(), "5".to_string())]);
+
+        let chat_request = request().with_field("chat_id", ChatID::from("@chat".to_string()));
+        assert_eq!(chat_request.fields["chat_id"], json!("@chat"));
+
+        assert!(request()
+            .with_input(ChatID::from(5))
+            .into_builder()
+            .is_err());
+    }
+}
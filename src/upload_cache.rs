@@ -0,0 +1,271 @@
+//! The UploadCache module remembers the file_id telegram
+//! gives to uploaded files, so the same content is only
+//! uploaded once.
+//!
+//! Files are identified by a hash of their content and the
+//! bot sending them, since a file_id is only valid for the
+//! bot that received it. Later sends of the same content
+//! by the same bot use the file_id instead.
+//! Ids rejected by telegram are forgotten and the file is
+//! uploaded again.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::error::BotError;
+use crate::input::{
+    FileUploader, FILE_UPLOAD_LIMIT, FILE_URL_LIMIT, PHOTO_UPLOAD_LIMIT, PHOTO_URL_LIMIT,
+};
+use crate::object::Message;
+
+/// A FileIdStore keeps the file_id of uploaded files
+/// by the key of their content.
+///
+/// The MemoryFileIdStore is enough for a single process,
+/// other stores can be used to survive restarts.
+pub trait FileIdStore: Send + Sync {
+    /// Get the file_id of a content key.
+    fn get(&self, key: &str) -> Option<String>;
+    /// Keep the file_id of a content key.
+    fn insert(&self, key: String, file_id: String);
+    /// Forget the file_id of a content key.
+    fn remove(&self, key: &str);
+}
+
+/// A FileIdStore backed by an in memory HashMap.
+#[derive(Debug, Default)]
+pub struct MemoryFileIdStore {
+    ids: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryFileIdStore {
+    pub fn new() -> Self {
+        MemoryFileIdStore::default()
+    }
+}
+
+impl FileIdStore for MemoryFileIdStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.ids.lock().ok().and_then(|ids| ids.get(key).cloned())
+    }
+
+    fn insert(&self, key: String, file_id: String) {
+        if let Ok(mut ids) = self.ids.lock() {
+            ids.insert(key, file_id);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Ok(mut ids) = self.ids.lock() {
+            ids.remove(key);
+        }
+    }
+}
+
+/// The kind of media a file was sent as, file ids
+/// of one kind cannot always be sent as another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaKind {
+    Photo,
+    Document,
+    Audio,
+    Video,
+    Voice,
+    VideoNote,
+    Animation,
+}
+
+impl MediaKind {
+    /// The name of the request field holding this kind of media.
+    pub fn field(self) -> &'static str {
+        match self {
+            MediaKind::Photo => "photo",
+            MediaKind::Document => "document",
+            MediaKind::Audio => "audio",
+            MediaKind::Video => "video",
+            MediaKind::Voice => "voice",
+            MediaKind::VideoNote => "video_note",
+            MediaKind::Animation => "animation",
+        }
+    }
+
+    /// The method sending this kind of media.
+    pub fn route(self) -> &'static str {
+        match self {
+            MediaKind::Photo => "sendPhoto",
+            MediaKind::Document => "sendDocument",
+            MediaKind::Audio => "sendAudio",
+            MediaKind::Video => "sendVideo",
+            MediaKind::Voice => "sendVoice",
+            MediaKind::VideoNote => "sendVideoNote",
+            MediaKind::Animation => "sendAnimation",
+        }
+    }
+
+    /// The largest file of this kind telegram accepts as an upload.
+    pub fn upload_limit(self) -> u64 {
+        match self {
+            MediaKind::Photo => PHOTO_UPLOAD_LIMIT,
+            _ => FILE_UPLOAD_LIMIT,
+        }
+    }
+
+    /// The largest file of this kind telegram downloads from an url.
+    ///
+    /// Urls are only checked against it when their server
+    /// reports a size, so the check is best effort.
+    pub fn url_limit(self) -> u64 {
+        match self {
+            MediaKind::Photo => PHOTO_URL_LIMIT,
+            _ => FILE_URL_LIMIT,
+        }
+    }
+
+    /// The file_id of this kind of media on a message,
+    /// the largest size for photos.
+    pub fn file_id(self, message: &Message) -> Option<String> {
+        match self {
+            MediaKind::Photo => message
+                .photo
+                .as_ref()
+                .and_then(|sizes| sizes.last())
+                .map(|size| size.file_id.clone()),
+            MediaKind::Document => message.document.as_ref().map(|d| d.file_id.clone()),
+            MediaKind::Audio => message.audio.as_ref().map(|a| a.file_id.clone()),
+            MediaKind::Video => message.video.as_ref().map(|v| v.file_id.clone()),
+            MediaKind::Voice => message.voice.as_ref().map(|v| v.file_id.clone()),
+            MediaKind::VideoNote => message.video_note.as_ref().map(|v| v.file_id.clone()),
+            MediaKind::Animation => message.animation.as_ref().map(|a| a.file_id.clone()),
+        }
+    }
+}
+
+/// The UploadCache substitutes uploads of known
+/// content by the file_id telegram gave to it.
+///
+/// ```
+/// use beepbop::bot::Bot;
+/// use beepbop::upload_cache::{MemoryFileIdStore, UploadCache};
+///
+/// let bot = Bot::new("API_KEY").with_upload_cache(UploadCache::new(MemoryFileIdStore::new()));
+/// ```
+#[derive(Clone)]
+pub struct UploadCache {
+    store: Arc<dyn FileIdStore>,
+}
+
+impl std::fmt::Debug for UploadCache {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "UploadCache")
+    }
+}
+
+impl UploadCache {
+    pub fn new<S: FileIdStore + 'static>(store: S) -> Self {
+        UploadCache {
+            store: Arc::new(store),
+        }
+    }
+
+    /// The store holding the file ids.
+    pub fn store(&self) -> &dyn FileIdStore {
+        self.store.as_ref()
+    }
+
+    /// The key of the content of a file sent by a bot as a
+    /// kind of media, streamed files have no key.
+    ///
+    /// Files on disk are read to hash them, so a file that is
+    /// not cached yet is read twice: once here and once to upload.
+    pub async fn key(
+        &self,
+        bot_id: &str,
+        kind: MediaKind,
+        uploader: &FileUploader,
+    ) -> std::io::Result<Option<String>> {
+        let hash = uploader.content_hash().await?;
+
+        Ok(hash.map(|hash| {
+            let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+            format!("{}:{}:{}", bot_id, kind.field(), hex)
+        }))
+    }
+}
+
+/// Whether telegram refused a file_id, which
+/// happens once the file is no longer available.
+pub(crate) fn is_wrong_file_id(err: &BotError) -> bool {
+    match err {
+        BotError::TelegramError(description) => {
+            description.to_lowercase().contains("wrong file identifier")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod upload_cache_tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn content_keys() {
+        let runtime = Runtime::new().expect("Unable to create a runtime");
+        let cache = UploadCache::new(MemoryFileIdStore::new());
+
+        let puppy = FileUploader::new("res/puppy.jpg").expect("Missing file");
+        let bytes = FileUploader::from_bytes("copy.jpg", std::fs::read("res/puppy.jpg").unwrap());
+        let other = FileUploader::from_bytes("copy.jpg", vec![1, 2, 3]);
+
+        let key = |kind, uploader| {
+            runtime
+                .block_on(cache.key("123", kind, uploader))
+                .unwrap()
+                .expect("Missing key")
+        };
+
+        assert_eq!(key(MediaKind::Photo, &puppy), key(MediaKind::Photo, &bytes));
+        assert_ne!(
+            key(MediaKind::Photo, &puppy),
+            key(MediaKind::Document, &puppy)
+        );
+        assert_ne!(key(MediaKind::Photo, &bytes), key(MediaKind::Photo, &other));
+
+        let other_bot = runtime
+            .block_on(cache.key("456", MediaKind::Photo, &puppy))
+            .unwrap();
+        assert_ne!(Some(key(MediaKind::Photo, &puppy)), other_bot);
+    }
+
+    #[test]
+    fn message_file_ids() {
+        let json = serde_json::json!({
+            "message_id": 1,
+            "date": 0,
+            "chat": {"id": 1, "type": "private"},
+            "photo": [
+                {"file_id": "small", "file_unique_id": "s", "width": 90, "height": 90},
+                {"file_id": "large", "file_unique_id": "l", "width": 800, "height": 800},
+            ],
+        });
+
+        let message: Message = serde_json::from_value(json).expect("Invalid message");
+
+        assert_eq!(
+            MediaKind::Photo.file_id(&message),
+            Some("large".to_string())
+        );
+        assert_eq!(MediaKind::Document.file_id(&message), None);
+    }
+
+    #[test]
+    fn wrong_file_ids() {
+        let refused = BotError::TelegramError(
+            "Telegram(400): Bad Request: wrong file identifier/HTTP URL specified".to_string(),
+        );
+        let other = BotError::TelegramError("Telegram(400): Bad Request: chat not found".into());
+
+        assert!(is_wrong_file_id(&refused));
+        assert!(!is_wrong_file_id(&other));
+    }
+}